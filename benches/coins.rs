@@ -0,0 +1,115 @@
+//!compares the indexed triangles tori are built with against the same triangles added one at a
+//!time with `add_tri`, on the first frame of the coins script in data/script.dw
+//!run with `cargo bench --bench coins`
+use std::mem::size_of;
+use std::time::{Duration, Instant};
+
+use graphics::{
+    move_matrix, rotx_matrix, Float, Light, Modtrix, RGB8Color, Screen, Space, Tessellation,
+    DEFAULT_RESOLUTION,
+};
+
+const RUNS: usize = 3;
+
+///where the four coins are, around the middle of the screen
+const COINS: [(Float, Float); 4] = [
+    (125.0, 125.0),
+    (-125.0, 125.0),
+    (125.0, -125.0),
+    (-125.0, -125.0),
+];
+
+fn setup(space: &mut Space<RGB8Color>) {
+    space.set_ambient_light((50, 50, 50).into());
+    space.add_light(Light::new(
+        (5000.0, 7500.0, 10000.0),
+        (255, 255, 255).into(),
+    ));
+    space.set_ambient_reflection((0.3, 0.3, 0.8));
+    space.set_diffuse_reflection((0.275, 0.275, 0.7));
+    space.set_specular_reflection((0.1, 0.1, 0.2));
+}
+
+fn coin(x: Float, y: Float) -> Modtrix {
+    let mut m = Modtrix::IDENT;
+    Modtrix::multr(&mut m, &move_matrix!(250.0 + x, 250.0 + y, 0.0));
+    Modtrix::multr(&mut m, &rotx_matrix!(90.0 as Float));
+    m
+}
+
+fn indexed() -> Space<RGB8Color> {
+    let mut space = Space::new();
+    setup(&mut space);
+    graphics::add_torus(
+        0.0,
+        0.0,
+        0.0,
+        30.0,
+        50.0,
+        Tessellation::Steps(DEFAULT_RESOLUTION),
+        &mut space,
+    );
+    space
+}
+
+///the same triangles with every corner stored separately
+fn soup() -> Space<RGB8Color> {
+    let from = indexed();
+    let mut space = Space::new();
+    setup(&mut space);
+    for [p, q, r] in from.triangles() {
+        space.add_tri(p, q, r);
+    }
+    space
+}
+
+///bytes used by the vertices, their normals, and the indices
+fn memory(space: &Space<RGB8Color>) -> usize {
+    2 * space.vertex_count() * size_of::<[Float; 4]>() + space.tri_count() * size_of::<[u32; 3]>()
+}
+
+///the fastest of a few runs of transforming and drawing every coin, which is the work done for
+///each frame
+fn time(space: &Space<RGB8Color>) -> (Duration, Duration) {
+    let mut best = (Duration::MAX, Duration::MAX);
+    for _ in 0..RUNS {
+        let mut screen = Screen::with_size(500, 500);
+        let (mut apply, mut draw) = (Duration::ZERO, Duration::ZERO);
+        for (x, y) in COINS {
+            let mut s = space.clone();
+            let start = Instant::now();
+            s.apply(&coin(x, y));
+            apply += start.elapsed();
+            let start = Instant::now();
+            graphics::space::draw_space(&s, &mut screen);
+            draw += start.elapsed();
+        }
+        best = (best.0.min(apply), best.1.min(draw));
+    }
+    best
+}
+
+fn report(name: &str, space: &Space<RGB8Color>) -> (usize, Duration) {
+    let mem = memory(space);
+    let (apply, draw) = time(space);
+    println!(
+        "{:<8} {:>8} vertices {:>8} triangles {:>7.1} MiB per coin   apply {:>8.2?}   draw {:>8.2?}",
+        name,
+        space.vertex_count(),
+        space.tri_count(),
+        mem as f64 / (1024.0 * 1024.0),
+        apply,
+        draw,
+    );
+    (mem, apply + draw)
+}
+
+fn main() {
+    let (soup_mem, soup_time) = report("add_tri", &soup());
+    let (mem, time) = report("indexed", &indexed());
+    println!(
+        "indexed uses {:.1}x less memory and is {:.2}x as fast",
+        soup_mem as f64 / mem as f64,
+        soup_time.as_secs_f64() / time.as_secs_f64()
+    );
+}
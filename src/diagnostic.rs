@@ -0,0 +1,240 @@
+//!problems found in a script, each with a stable code and, when it is known, where in the source
+//!the problem is so it can be shown with a snippet of the offending line
+
+use std::error::Error;
+use std::fmt;
+
+///how bad a diagnostic is, errors stop the script from being run
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+///every kind of problem that can be reported
+///the codes are stable so once a code is handed out it should never be reused for something else
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Code {
+    UnexpectedCharacter,
+    InvalidNumber,
+    ExpectedCommand,
+    ExpectedNumber,
+    ExpectedName,
+    InvalidAxis,
+    UndefinedConstants,
+    InvalidShading,
+    ExpectedColon,
+    InvalidFrame,
+    VaryWithoutFrames,
+    UnknownKnob,
+    UnbalancedPop,
+    CameraLooksAtItself,
+    InvalidFocal,
+    InvalidResolution,
+    MeshLoadFailed,
+    UnknownCoordSystem,
+    UnknownKnobList,
+    InvalidEasing,
+    InvalidSpline,
+    InvalidFrameRange,
+    AnimationSaveFailed,
+    Unsupported,
+    MissingBasename,
+    UnusedBasename,
+    OverlappingVary,
+}
+
+impl Code {
+    ///the code as it is printed, `E` codes are errors and `W` codes are warnings
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::UnexpectedCharacter => "E0001",
+            Self::InvalidNumber => "E0002",
+            Self::ExpectedCommand => "E0003",
+            Self::ExpectedNumber => "E0004",
+            Self::ExpectedName => "E0005",
+            Self::InvalidAxis => "E0006",
+            Self::UndefinedConstants => "E0007",
+            Self::InvalidShading => "E0008",
+            Self::ExpectedColon => "E0009",
+            Self::InvalidFrame => "E0010",
+            Self::VaryWithoutFrames => "E0011",
+            Self::UnknownKnob => "E0012",
+            Self::UnbalancedPop => "E0013",
+            Self::CameraLooksAtItself => "E0014",
+            Self::InvalidFocal => "E0015",
+            Self::InvalidResolution => "E0016",
+            Self::MeshLoadFailed => "E0017",
+            Self::UnknownCoordSystem => "E0018",
+            Self::UnknownKnobList => "E0019",
+            Self::InvalidEasing => "E0020",
+            Self::InvalidSpline => "E0021",
+            Self::InvalidFrameRange => "E0022",
+            Self::AnimationSaveFailed => "E0023",
+            Self::Unsupported => "W0001",
+            Self::MissingBasename => "W0002",
+            Self::UnusedBasename => "W0003",
+            Self::OverlappingVary => "W0004",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        if self.as_str().starts_with('E') {
+            Severity::Error
+        } else {
+            Severity::Warning
+        }
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+///a place in the source, line and column start at 1 and len is how many characters to underline
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub len: usize,
+}
+
+impl Span {
+    pub fn new(line: usize, column: usize, len: usize) -> Self {
+        Self { line, column, len }
+    }
+}
+
+///a single problem
+#[derive(Clone, PartialEq, Debug)]
+pub struct Diagnostic {
+    pub code: Code,
+    pub message: String,
+    ///None when the problem does not come from source text, like a script read from an
+    ///intermediate file
+    pub span: Option<Span>,
+}
+
+impl Diagnostic {
+    pub fn new(code: Code, span: Option<Span>, message: String) -> Self {
+        Self {
+            code,
+            message,
+            span,
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.code.severity()
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.severity() {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}[{}]: {}", kind, self.code, self.message)
+    }
+}
+
+impl Error for Diagnostic {}
+
+///collects every problem found in one script so they can all be reported together
+///rendering it with `Display` prints each problem with the file, line, column, and a snippet
+#[derive(Clone, Debug, Default)]
+pub struct Diagnostics {
+    file: String,
+    source: String,
+    items: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    ///creates an empty collection for problems in `source`, which was read from `file`
+    pub fn with_source(file: &str, source: &str) -> Self {
+        Self {
+            file: file.to_string(),
+            source: source.to_string(),
+            items: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, d: Diagnostic) {
+        self.items.push(d);
+    }
+
+    pub fn report(&mut self, code: Code, span: Option<Span>, message: String) {
+        self.push(Diagnostic::new(code, span, message));
+    }
+
+    pub fn items(&self) -> &[Diagnostic] {
+        &self.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|d| d.severity() == Severity::Error)
+            .count()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.error_count() > 0
+    }
+
+    fn write_snippet(&self, f: &mut fmt::Formatter<'_>, span: Span) -> fmt::Result {
+        let text = match self.source.lines().nth(span.line.saturating_sub(1)) {
+            Some(text) => text,
+            None => return Ok(()),
+        };
+        let gutter = " ".repeat(span.line.to_string().len());
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", span.line, text)?;
+        //keep tabs so the caret lines up with the text above it
+        let pad: String = text
+            .chars()
+            .take(span.column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        writeln!(f, "{} | {}{}", gutter, pad, "^".repeat(span.len.max(1)))
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        //problems are found in more than one pass so put them back in the order of the source
+        let mut items: Vec<_> = self.items.iter().collect();
+        items.sort_by_key(|d| d.span.map_or((usize::MAX, 0), |s| (s.line, s.column)));
+        for d in items {
+            writeln!(f, "{}", d)?;
+            match d.span {
+                Some(span) => {
+                    writeln!(f, " --> {}:{}:{}", self.file, span.line, span.column)?;
+                    self.write_snippet(f, span)?;
+                }
+                None if !self.file.is_empty() => writeln!(f, " --> {}", self.file)?,
+                None => (),
+            }
+            writeln!(f)?;
+        }
+        let errors = self.error_count();
+        if errors > 0 {
+            writeln!(
+                f,
+                "could not run script due to {} error{}",
+                errors,
+                if errors == 1 { "" } else { "s" }
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for Diagnostics {}
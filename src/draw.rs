@@ -1,27 +1,136 @@
 //!functions to draw different shapes to a space
 
 use crate::screen::Color;
-use crate::space::{Float, Point, Space};
+use crate::space::{Float, Modtrix, Point, Space};
 
-const RESOLUTION: usize = 500;
+///how many steps curves and round shapes are made of when nothing else is asked for
+pub const DEFAULT_RESOLUTION: usize = 500;
+///the fewest steps a round shape can be made of and still enclose anything, a sphere's first and
+///last half circles are in the same place so it needs 4 to have 3 different ones
+pub const MIN_RESOLUTION: usize = 4;
+///the most steps a shape split to a tolerance is made of, however close it is
+pub const MAX_RESOLUTION: usize = 1000;
+///how many pixels of a shape's outline each step covers when the resolution is picked from its
+///size on the screen
+const PIXELS_PER_STEP: Float = 3.0;
+///how many times a curve is split into halves at most when it is split to a tolerance
+const MAX_DEPTH: u32 = 10;
+///how many pieces a curve starts as before they are split to a tolerance
+const FIRST_PIECES: usize = 4;
+
+///how finely curves and round shapes are split into lines and triangles
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Resolution {
+    ///the same number of steps around every shape no matter how big it is
+    Steps(usize),
+    ///as many steps as the size of the shape on the screen needs, so small or distant shapes
+    ///are cheap and close ones are still smooth
+    Auto,
+    ///split each shape until no line or triangle is more than this many pixels from the real
+    ///shape on the screen
+    Tolerance(Float),
+}
+
+///how one shape is split into lines or triangles
+#[derive(Clone, Copy, Debug)]
+pub enum Tessellation<'a> {
+    ///this many steps each way around
+    Steps(usize),
+    ///as many as it takes for every line or triangle to be within `tolerance` pixels of the real
+    ///shape, once `transform` and then the space's view and projection are applied
+    Within {
+        tolerance: Float,
+        transform: &'a Modtrix,
+    },
+}
+
+impl Default for Resolution {
+    fn default() -> Self {
+        Self::Steps(DEFAULT_RESOLUTION)
+    }
+}
+
+impl Resolution {
+    ///how to split a round shape with the given center and radius which is about to be
+    ///transformed by `transform` and added to `space`
+    pub fn tessellation<'a, T: Color>(
+        self,
+        transform: &'a Modtrix,
+        space: &Space<T>,
+        center: Point,
+        radius: Float,
+    ) -> Tessellation<'a> {
+        match self {
+            Self::Steps(n) => {
+                assert!(n >= MIN_RESOLUTION, "resolution of {} is too low", n);
+                Tessellation::Steps(n)
+            }
+            Self::Auto => {
+                let px = space
+                    .screen_radius(transform.transform(center), radius * transform.max_scale());
+                Tessellation::Steps(steps_for_radius(px))
+            }
+            Self::Tolerance(tolerance) => {
+                assert!(tolerance > 0.0, "tolerance of {} is too low", tolerance);
+                Tessellation::Within {
+                    tolerance,
+                    transform,
+                }
+            }
+        }
+    }
+}
+
+///the number of steps around a circle which is `radius` pixels across on the screen
+pub fn steps_for_radius(radius: Float) -> usize {
+    let steps = (std::f64::consts::TAU * radius / PIXELS_PER_STEP).ceil();
+    if steps.is_nan() {
+        return DEFAULT_RESOLUTION;
+    }
+    //the clamp is done as a float so huge or infinite sizes don't overflow the cast
+    steps.clamp(MIN_RESOLUTION as Float, DEFAULT_RESOLUTION as Float) as usize
+}
 
 ///Adds a circle to a given fatrix
 ///circle defined by its center point (cx, cy, cz) and a radius, r
-pub fn add_circle<T: Color>(cx: Float, cy: Float, cz: Float, r: Float, edges: &mut Space<T>) {
-    for t in 0..RESOLUTION {
-        //RESOLUTION should be reasonable enough that these type casts are fine
-        let t0 = (t as Float) / (RESOLUTION as Float) * std::f64::consts::TAU;
-        let t1 = ((t + 1) as Float) / (RESOLUTION as Float) * std::f64::consts::TAU;
-        let x0 = r * t0.cos() + cx;
-        let y0 = r * t0.sin() + cy;
-        let x1 = r * t1.cos() + cx;
-        let y1 = r * t1.sin() + cy;
-        edges.add_line((x0, y0, cz), (x1, y1, cz));
-    }
+pub fn add_circle<T: Color>(
+    cx: Float,
+    cy: Float,
+    cz: Float,
+    r: Float,
+    tess: Tessellation<'_>,
+    edges: &mut Space<T>,
+) {
+    let f = |t: Float| {
+        let t = t * std::f64::consts::TAU;
+        (r * t.cos() + cx, r * t.sin() + cy, cz)
+    };
+    add_curve(f, tess, edges);
+}
+
+///the value at `t`, from 0 to 1, of a cubic going from `p0` to `p1` which leaves `p0` with slope
+///`r0` and comes into `p1` with slope `r1`
+pub fn hermite(p0: Float, p1: Float, r0: Float, r1: Float, t: Float) -> Float {
+    let a = 2.0 * p0 - 2.0 * p1 + r0 + r1;
+    let b = -3.0 * p0 + 3.0 * p1 - 2.0 * r0 - r1;
+    let c = r0;
+    let d = p0;
+    a * t * t * t + b * t * t + c * t + d
+}
+
+///the value at `t`, from 0 to 1, of a cubic bezier going from `p0` to `p3` pulled towards `p1` and
+///`p2`
+pub fn bezier(p0: Float, p1: Float, p2: Float, p3: Float, t: Float) -> Float {
+    let a = -p0 + 3.0 * p1 - 3.0 * p2 + p3;
+    let b = 3.0 * p0 - 6.0 * p1 + 3.0 * p2;
+    let c = -3.0 * p0 + 3.0 * p1;
+    let d = p0;
+    a * t * t * t + b * t * t + c * t + d
 }
 
 ///Adds a hermite curve defined by a start and end point and slopes coming out of or into those
 ///points
+#[allow(clippy::too_many_arguments)]
 pub fn add_hermite<T: Color>(
     x0: Float,
     y0: Float,
@@ -31,31 +140,22 @@ pub fn add_hermite<T: Color>(
     ry0: Float,
     rx1: Float,
     ry1: Float,
+    tess: Tessellation<'_>,
     edges: &mut Space<T>,
 ) {
-    let ax = 2.0 * x0 - 2.0 * x1 + rx0 + rx1;
-    let bx = -3.0 * x0 + 3.0 * x1 - 2.0 * rx0 - rx1;
-    let cx = rx0;
-    let dx = x0;
-    let ay = 2.0 * y0 - 2.0 * y1 + ry0 + ry1;
-    let by = -3.0 * y0 + 3.0 * y1 - 2.0 * ry0 - ry1;
-    let cy = ry0;
-    let dy = y0;
-    let fx = |x| ax * x * x * x + bx * x * x + cx * x + dx;
-    let fy = |y| ay * y * y * y + by * y * y + cy * y + dy;
-    for t in 0..RESOLUTION {
-        let t0 = (t as Float) / (RESOLUTION as Float);
-        let t1 = ((t + 1) as Float) / (RESOLUTION as Float);
-        let px0 = fx(t0);
-        let py0 = fy(t0);
-        let px1 = fx(t1);
-        let py1 = fy(t1);
-        edges.add_line((px0, py0, 0.0), (px1, py1, 0.0));
-    }
+    let f = |t| {
+        (
+            hermite(x0, x1, rx0, rx1, t),
+            hermite(y0, y1, ry0, ry1, t),
+            0.0,
+        )
+    };
+    add_curve(f, tess, edges);
 }
 
 ///adds bezier curve to fatrix with (x0, y0) and (x3, y3) as start and end points and the other two
 ///points control points
+#[allow(clippy::too_many_arguments)]
 pub fn add_bezier<T: Color>(
     x0: Float,
     y0: Float,
@@ -65,28 +165,90 @@ pub fn add_bezier<T: Color>(
     y2: Float,
     x3: Float,
     y3: Float,
+    tess: Tessellation<'_>,
     edges: &mut Space<T>,
 ) {
-    let ax = -x0 + 3.0 * x1 - 3.0 * x2 + x3;
-    let bx = 3.0 * x0 - 6.0 * x1 + 3.0 * x2;
-    let cx = -3.0 * x0 + 3.0 * x1;
-    let dx = x0;
-    let ay = -y0 + 3.0 * y1 - 3.0 * y2 + y3;
-    let by = 3.0 * y0 - 6.0 * y1 + 3.0 * y2;
-    let cy = -3.0 * y0 + 3.0 * y1;
-    let dy = y0;
-    let fx = |x| ax * x * x * x + bx * x * x + cx * x + dx;
-    let fy = |y| ay * y * y * y + by * y * y + cy * y + dy;
-    for t in 0..RESOLUTION {
+    let f = |t| (bezier(x0, x1, x2, x3, t), bezier(y0, y1, y2, y3, t), 0.0);
+    add_curve(f, tess, edges);
+}
+
+///adds the curve `f` goes along as `t` goes from 0 to 1 as a run of lines
+fn add_curve<T: Color>(f: impl Fn(Float) -> Point, tess: Tessellation<'_>, edges: &mut Space<T>) {
+    let ts: Vec<Float> = match tess {
         //cast should be fine if resolution is not stupid
-        let t0 = (t as Float) / (RESOLUTION as Float);
-        let t1 = ((t + 1) as Float) / (RESOLUTION as Float);
-        let px0 = fx(t0);
-        let py0 = fy(t0);
-        let px1 = fx(t1);
-        let py1 = fy(t1);
-        edges.add_line((px0, py0, 0.0), (px1, py1, 0.0));
+        Tessellation::Steps(steps) => (0..=steps)
+            .map(|t| (t as Float) / (steps as Float))
+            .collect(),
+        Tessellation::Within {
+            tolerance,
+            transform,
+        } => {
+            let screen = |t| edges.screen_point(transform.transform(f(t)));
+            let mut ts = vec![0.0];
+            //a few pieces to start with so the ends of a closed curve aren't taken for a line
+            let mut prev = (0.0, screen(0.0));
+            for k in 1..=FIRST_PIECES {
+                let t = k as Float / FIRST_PIECES as Float;
+                let next = (t, screen(t));
+                subdivide(&screen, tolerance, prev, next, 0, &mut ts);
+                prev = next;
+            }
+            ts
+        }
+    };
+    for w in ts.windows(2) {
+        edges.add_line(f(w[0]), f(w[1]));
+    }
+}
+
+///splits the piece of a curve from `t0` to `t1` in half until the middle of each piece is within
+///`tolerance` pixels of the middle of the line drawn for it, pushing where each piece ends
+fn subdivide(
+    screen: &impl Fn(Float) -> Point,
+    tolerance: Float,
+    (t0, s0): (Float, Point),
+    (t1, s1): (Float, Point),
+    depth: u32,
+    ts: &mut Vec<Float>,
+) {
+    let tm = (t0 + t1) / 2.0;
+    let sm = screen(tm);
+    //only the position on the screen matters, not the depth
+    let off = (sm.0 - (s0.0 + s1.0) / 2.0).hypot(sm.1 - (s0.1 + s1.1) / 2.0);
+    if depth < MAX_DEPTH && off > tolerance {
+        subdivide(screen, tolerance, (t0, s0), (tm, sm), depth + 1, ts);
+        subdivide(screen, tolerance, (tm, sm), (t1, s1), depth + 1, ts);
+    } else {
+        ts.push(t1);
+    }
+}
+
+///how many pieces a circular arc of `angle` radians needs so no piece strays more than `tolerance`
+///from it, the circle being `radius` pixels across on the screen
+fn pieces_within(angle: Float, radius: Float, tolerance: Float) -> usize {
+    //a piece spanning an angle of 2h is at most radius * (1 - cos h) from the arc
+    let h = (1.0 - tolerance / radius).max(-1.0).acos();
+    let pieces = (angle / (2.0 * h)).ceil();
+    if pieces.is_nan() {
+        return MAX_RESOLUTION;
     }
+    pieces.clamp(MIN_RESOLUTION as Float, MAX_RESOLUTION as Float) as usize
+}
+
+///how many pixels on the screen one unit before `transform` is, at the point of a ball around
+///`center` with radius `bound` nearest the eye
+fn pixels_per_unit<T: Color>(
+    edges: &Space<T>,
+    transform: &Modtrix,
+    center: Point,
+    bound: Float,
+) -> Float {
+    let scale = transform.max_scale();
+    let bound = bound * scale;
+    if bound == 0.0 {
+        return scale;
+    }
+    edges.screen_radius(transform.transform(center), bound) / bound * scale
 }
 
 ///adds a box to the given fatrix given the front top left corner x, y, z and a width, height, and
@@ -124,58 +286,112 @@ pub fn add_box<T: Color>(
     edges.add_tri((x, y - h, z), (x, y - h, z - d), (x + w, y - h, z - d));
 }
 
-///returns a vector of the points on the sphere
-fn sphere_points(x: Float, y: Float, z: Float, r: Float) -> Vec<Point> {
+///returns a vector of the points on the sphere, each with the normal of the sphere there
+///`around` half circles from pole to pole each with `along` points
+fn sphere_points(
+    x: Float,
+    y: Float,
+    z: Float,
+    r: Float,
+    around: usize,
+    along: usize,
+) -> Vec<(Point, Point)> {
     //this conversion should be fine as long as usize isn't stupid as well
-    let mut out = Vec::with_capacity(RESOLUTION * RESOLUTION);
-    for p in 0..RESOLUTION {
-        for t in 0..=RESOLUTION - 1 {
+    let mut out = Vec::with_capacity(around * along);
+    for p in 0..around {
+        for t in 0..along {
             //cast should be fine as resolution is not stupid
-            let phi = (p as Float) / ((RESOLUTION - 1) as Float) * std::f64::consts::TAU;
-            let theta = (t as Float) / ((RESOLUTION - 1) as Float) * std::f64::consts::PI;
+            let phi = (p as Float) / ((around - 1) as Float) * std::f64::consts::TAU;
+            let theta = (t as Float) / ((along - 1) as Float) * std::f64::consts::PI;
             let px = r * theta.cos() + x;
             let py = r * theta.sin() * phi.cos() + y;
             let pz = r * theta.sin() * phi.sin() + z;
-            out.push((px, py, pz));
+            let nrm = (
+                theta.cos(),
+                theta.sin() * phi.cos(),
+                theta.sin() * phi.sin(),
+            );
+            out.push(((px, py, pz), nrm));
         }
     }
     out
 }
 
 ///adds a sphere to a fatrix given a center (x, y, z) and a radius r
-pub fn add_sphere<T: Color>(x: Float, y: Float, z: Float, r: Float, edges: &mut Space<T>) {
-    let p = sphere_points(x, y, z, r);
+pub fn add_sphere<T: Color>(
+    x: Float,
+    y: Float,
+    z: Float,
+    r: Float,
+    tess: Tessellation<'_>,
+    edges: &mut Space<T>,
+) {
+    //the first and last half circles are in the same place, so there is one more of each than
+    //there are pieces
+    let (around, along) = match tess {
+        Tessellation::Steps(steps) => (steps, steps),
+        Tessellation::Within {
+            tolerance,
+            transform,
+        } => {
+            let radius = r * pixels_per_unit(edges, transform, (x, y, z), r);
+            (
+                pieces_within(std::f64::consts::TAU, radius, tolerance) + 1,
+                pieces_within(std::f64::consts::PI, radius, tolerance) + 1,
+            )
+        }
+    };
+    let p: Vec<u32> = sphere_points(x, y, z, r, around, along)
+        .into_iter()
+        .map(|(v, nrm)| edges.add_vertex(v, Some(nrm)))
+        .collect();
     let n = p.len();
-    for i in 0..RESOLUTION {
-        for j in 0..RESOLUTION - 1 {
-            let o1 = (j + i * RESOLUTION) % n;
-            let o2 = (j + 1 + i * RESOLUTION) % n;
-            let o3 = (j + RESOLUTION + 1 + i * RESOLUTION) % n;
-            let o4 = (j + RESOLUTION + i * RESOLUTION) % n;
+    for i in 0..around {
+        for j in 0..along - 1 {
+            let o1 = (j + i * along) % n;
+            let o2 = (j + 1 + i * along) % n;
+            let o3 = (j + along + 1 + i * along) % n;
+            let o4 = (j + along + i * along) % n;
 
             if j == 0 {
-                edges.add_tri(p[o1], p[o2], p[o3]);
-            } else if j + 1 == RESOLUTION - 1 {
-                edges.add_tri(p[o1], p[o3], p[o4]);
+                edges.add_indexed_tri(p[o1], p[o2], p[o3]);
+            } else if j + 1 == along - 1 {
+                edges.add_indexed_tri(p[o1], p[o3], p[o4]);
             } else {
-                edges.add_tri(p[o1], p[o2], p[o3]);
-                edges.add_tri(p[o1], p[o3], p[o4]);
+                edges.add_indexed_tri(p[o1], p[o2], p[o3]);
+                edges.add_indexed_tri(p[o1], p[o3], p[o4]);
             }
         }
     }
 }
 
-fn torus_points(x: Float, y: Float, z: Float, r1: Float, r2: Float) -> Vec<Point> {
-    let mut out = Vec::with_capacity(RESOLUTION * RESOLUTION);
-    for p in 0..RESOLUTION {
-        for t in 0..RESOLUTION {
-            let phi = (p as Float) / (RESOLUTION as Float) * std::f64::consts::TAU;
-            let theta = (t as Float) / (RESOLUTION as Float) * std::f64::consts::TAU;
+///returns a vector of the points on the torus, each with the normal of the torus there
+///`ring` cross sections each with `tube` points
+fn torus_points(
+    x: Float,
+    y: Float,
+    z: Float,
+    r1: Float,
+    r2: Float,
+    ring: usize,
+    tube: usize,
+) -> Vec<(Point, Point)> {
+    let mut out = Vec::with_capacity(ring * tube);
+    for p in 0..ring {
+        for t in 0..tube {
+            let phi = (p as Float) / (ring as Float) * std::f64::consts::TAU;
+            let theta = (t as Float) / (tube as Float) * std::f64::consts::TAU;
             let px = phi.cos() * (r1 * theta.cos() + r2) + x;
             let py = r1 * theta.sin() + y;
             let pz = phi.sin() * (r1 * theta.cos() + r2) + z;
+            //straight out from the middle of the cross section
+            let nrm = (
+                phi.cos() * theta.cos(),
+                theta.sin(),
+                phi.sin() * theta.cos(),
+            );
 
-            out.push((px, py, pz));
+            out.push(((px, py, pz), nrm));
         }
     }
     out
@@ -189,18 +405,35 @@ pub fn add_torus<T: Color>(
     z: Float,
     r1: Float,
     r2: Float,
+    tess: Tessellation<'_>,
     edges: &mut Space<T>,
 ) {
-    let p = torus_points(x, y, z, r1, r2);
+    let (ring, tube) = match tess {
+        Tessellation::Steps(steps) => (steps, steps),
+        Tessellation::Within {
+            tolerance,
+            transform,
+        } => {
+            let px = pixels_per_unit(edges, transform, (x, y, z), r1 + r2);
+            (
+                pieces_within(std::f64::consts::TAU, (r1 + r2) * px, tolerance),
+                pieces_within(std::f64::consts::TAU, r1 * px, tolerance),
+            )
+        }
+    };
+    let p: Vec<u32> = torus_points(x, y, z, r1, r2, ring, tube)
+        .into_iter()
+        .map(|(v, nrm)| edges.add_vertex(v, Some(nrm)))
+        .collect();
     let n = p.len();
-    for i in 0..RESOLUTION {
-        for j in 0..RESOLUTION {
-            let o1 = (j + i * RESOLUTION) % n;
-            let o2 = ((j + 1) % RESOLUTION + i * RESOLUTION) % n;
-            let o3 = ((j + 1) % RESOLUTION + RESOLUTION + i * RESOLUTION) % n;
-            let o4 = (j + RESOLUTION + i * RESOLUTION) % n;
-            edges.add_tri(p[o1], p[o2], p[o3]);
-            edges.add_tri(p[o1], p[o3], p[o4]);
+    for i in 0..ring {
+        for j in 0..tube {
+            let o1 = (j + i * tube) % n;
+            let o2 = ((j + 1) % tube + i * tube) % n;
+            let o3 = ((j + 1) % tube + tube + i * tube) % n;
+            let o4 = (j + tube + i * tube) % n;
+            edges.add_indexed_tri(p[o1], p[o2], p[o3]);
+            edges.add_indexed_tri(p[o1], p[o3], p[o4]);
         }
     }
 }
@@ -0,0 +1,90 @@
+//!timing functions for `vary`, which say how far along a knob is at each point of its range
+
+use std::f64::consts::PI;
+
+use crate::{draw, Float};
+
+///how a knob moves from its start value to its end value
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Easing {
+    ///the same amount every frame
+    Linear,
+    ///starts slow, the same curve as css `ease-in`
+    EaseIn,
+    ///ends slow, the same curve as css `ease-out`
+    EaseOut,
+    ///starts and ends slow, the same curve as css `ease-in-out`
+    EaseInOut,
+    ///starts and ends slow along a cubic, sharper in the middle than `EaseInOut`
+    Cubic,
+    ///starts and ends slow along half a cosine wave
+    Sine,
+    ///falls to the end value and bounces off it three times, lower each time
+    Bounce,
+    ///jumps this many times, holding each value until the next jump, the last jump lands on the
+    ///last frame
+    Step(u32),
+    ///a css style cubic bezier from (0, 0) to (1, 1) with control points (x1, y1) and (x2, y2),
+    ///x1 and x2 must be from 0 to 1 so there is only one value for each point in time
+    CubicBezier(Float, Float, Float, Float),
+}
+
+impl Easing {
+    ///how far along the knob is, 0 is the start value and 1 the end, when `t` of the way through
+    ///the range, `t` is from 0 to 1
+    pub fn apply(&self, t: Float) -> Float {
+        match *self {
+            Easing::Linear => t,
+            Easing::EaseIn => bezier(0.42, 0.0, 1.0, 1.0, t),
+            Easing::EaseOut => bezier(0.0, 0.0, 0.58, 1.0, t),
+            Easing::EaseInOut => bezier(0.42, 0.0, 0.58, 1.0, t),
+            Easing::Cubic if t < 0.5 => 4.0 * t * t * t,
+            Easing::Cubic => 1.0 - (2.0 - 2.0 * t).powi(3) / 2.0,
+            Easing::Sine => (1.0 - (PI * t).cos()) / 2.0,
+            Easing::Bounce => bounce(t),
+            Easing::Step(n) => (t * n as Float).floor() / n as Float,
+            Easing::CubicBezier(x1, y1, x2, y2) => bezier(x1, y1, x2, y2, t),
+        }
+    }
+}
+
+fn bezier(x1: Float, y1: Float, x2: Float, y2: Float, t: Float) -> Float {
+    draw::bezier(0.0, y1, y2, 1.0, along(x1, x2, t))
+}
+
+///how far along a cubic bezier from (0, 0) to (1, 1), with control points at `x1` and `x2` across,
+///the curve is `t` across, `x1` and `x2` must be from 0 to 1
+pub(crate) fn along(x1: Float, x2: Float, t: Float) -> Float {
+    if t <= 0.0 || t >= 1.0 {
+        return t;
+    }
+    //x only ever goes up when x1 and x2 are from 0 to 1, so halving finds the one point at t
+    let (mut lo, mut hi) = (0.0, 1.0);
+    for _ in 0..60 {
+        let mid = (lo + hi) / 2.0;
+        if draw::bezier(0.0, x1, x2, 1.0, mid) < t {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+///the usual bounce, a parabola for the fall and then three smaller ones
+fn bounce(t: Float) -> Float {
+    const N: Float = 7.5625;
+    const D: Float = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
@@ -1,14 +1,40 @@
 use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
 use std::fs::File;
+use std::sync::Arc;
 
-use crate::{Color, Light, Modtrix, Screen, Space};
+use crate::gif::{self, Gif, GifOptions};
+use crate::{
+    Color, Easing, Light, Mesh, MeshError, Modtrix, Projection, Resolution, Screen, Shading, Space,
+    Spline,
+};
 
 #[derive(Debug, Clone)]
 pub struct Engine<T: Color> {
     stack: Vec<Modtrix>,
     space: Space<T>,
     screen: Screen<T>,
-    frames: HashMap<String, Vec<f64>>,
+    ///what `vary` and `tween` give each knob in each frame, None in frames between their ranges
+    frames: HashMap<String, Vec<Option<f64>>>,
+    ///knobs given by keyframes, used for knobs with no `vary` or `tween`
+    splines: HashMap<String, Spline>,
+    ///the value of every knob no animation is given for, set with `set_knob`
+    knobs: HashMap<String, f64>,
+    ///copies of `knobs` saved with `save_knobs`, by name
+    knob_lists: HashMap<String, HashMap<String, f64>>,
+    ///whether the eye was placed with `look_at`
+    has_camera: bool,
+    ///how finely round shapes are drawn when they don't ask for anything else
+    resolution: Resolution,
+    ///meshes already read by `load_mesh`, by file name, shared between the clones made for frames
+    meshes: HashMap<String, Arc<Mesh>>,
+    ///copies of the top of the stack saved with `save_coord_system`, by name
+    coord_systems: HashMap<String, Modtrix>,
+    ///every triangle drawn since `start_export`, as it was before the view was applied
+    export: Option<Mesh>,
+    ///how animations are saved
+    gif: GifOptions,
 }
 
 impl<T: Color> Engine<T> {
@@ -19,6 +45,15 @@ impl<T: Color> Engine<T> {
             space: Space::new(),
             screen: Screen::<T>::with_size(screen_width, screen_height),
             frames: HashMap::new(),
+            splines: HashMap::new(),
+            knobs: HashMap::new(),
+            knob_lists: HashMap::new(),
+            has_camera: false,
+            resolution: Resolution::default(),
+            meshes: HashMap::new(),
+            coord_systems: HashMap::new(),
+            export: None,
+            gif: GifOptions::default(),
         }
     }
 
@@ -41,6 +76,23 @@ impl<T: Color> Engine<T> {
         self.stack.pop();
     }
 
+    ///saves a copy of the top of the stack which shapes can be drawn in later, after the stack has
+    ///moved on, a name saved again is replaced
+    pub fn save_coord_system(&mut self, name: &str) {
+        let top = self.stack.last().unwrap().clone();
+        self.coord_systems.insert(name.to_string(), top);
+    }
+
+    ///pushes a copy of a saved coordinate system, so what is added until the next `pop_sys` is
+    ///drawn in it instead of the top of the stack
+    pub fn push_coord_system(&mut self, name: &str) {
+        let sys = match self.coord_systems.get(name) {
+            Some(sys) => sys.clone(),
+            None => panic!("coordinate system `{}` was never saved", name),
+        };
+        self.stack.push(sys);
+    }
+
     pub fn move_sys(&mut self, x: f64, y: f64, z: f64) {
         let mm = crate::move_matrix!(x, y, z);
         Modtrix::multr(self.stack.last_mut().unwrap(), &mm);
@@ -69,20 +121,109 @@ impl<T: Color> Engine<T> {
         crate::add_box(x, y, z, w, h, d, &mut self.space);
     }
 
-    pub fn add_sphere(&mut self, (x, y, z): (f64, f64, f64), r: f64) {
-        crate::add_sphere(x, y, z, r, &mut self.space)
+    pub fn add_sphere(&mut self, c: (f64, f64, f64), r: f64) {
+        self.add_sphere_with(c, r, self.resolution);
+    }
+
+    ///adds a sphere with its own resolution instead of the engine's
+    pub fn add_sphere_with(&mut self, (x, y, z): (f64, f64, f64), r: f64, res: Resolution) {
+        let tess = res.tessellation(self.stack.last().unwrap(), &self.space, (x, y, z), r);
+        crate::add_sphere(x, y, z, r, tess, &mut self.space)
+    }
+
+    pub fn add_torus(&mut self, c: (f64, f64, f64), r0: f64, r1: f64) {
+        self.add_torus_with(c, r0, r1, self.resolution);
+    }
+
+    ///adds a torus with its own resolution instead of the engine's
+    pub fn add_torus_with(
+        &mut self,
+        (x, y, z): (f64, f64, f64),
+        r0: f64,
+        r1: f64,
+        res: Resolution,
+    ) {
+        let top = self.stack.last().unwrap();
+        let tess = res.tessellation(top, &self.space, (x, y, z), r0 + r1);
+        crate::add_torus(x, y, z, r0, r1, tess, &mut self.space)
+    }
+
+    ///how finely the shapes added after this are drawn unless they say otherwise
+    pub fn set_resolution(&mut self, res: Resolution) {
+        self.resolution = res;
+    }
+
+    pub fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+
+    ///adds every triangle of a mesh, under the current transformation once `apply_sys` is called
+    pub fn add_mesh(&mut self, mesh: &Mesh) {
+        mesh.add_to(&mut self.space);
+    }
+
+    ///reads a mesh file so `add_mesh_file` can add it, a file is only read the first time
+    pub fn load_mesh(&mut self, file: &str) -> Result<(), MeshError> {
+        if !self.meshes.contains_key(file) {
+            let mesh = Mesh::load(file)?;
+            self.meshes.insert(file.to_string(), Arc::new(mesh));
+        }
+        Ok(())
     }
 
-    pub fn add_torus(&mut self, (x, y, z): (f64, f64, f64), r0: f64, r1: f64) {
-        crate::add_torus(x, y, z, r0, r1, &mut self.space)
+    ///adds a mesh file, reading it first if `load_mesh` hasn't already
+    pub fn add_mesh_file(&mut self, file: &str) -> Result<(), MeshError> {
+        self.load_mesh(file)?;
+        let mesh = Arc::clone(&self.meshes[file]);
+        self.add_mesh(&mesh);
+        Ok(())
     }
 
     pub fn add_line(&mut self, p: (f64, f64, f64), q: (f64, f64, f64)) {
         crate::add_line(p, q, &mut self.space)
     }
 
+    ///adds a line with each end in its own saved coordinate system, or the top of the stack for
+    ///None, the ends are transformed here so the line must not be moved again with `apply_sys`
+    pub fn add_line_in(
+        &mut self,
+        p: (f64, f64, f64),
+        p_sys: Option<&str>,
+        q: (f64, f64, f64),
+        q_sys: Option<&str>,
+    ) {
+        let sys = |name: Option<&str>| match name {
+            None => self.stack.last().unwrap(),
+            Some(name) => match self.coord_systems.get(name) {
+                Some(sys) => sys,
+                None => panic!("coordinate system `{}` was never saved", name),
+            },
+        };
+        let (p, q) = (sys(p_sys).transform(p), sys(q_sys).transform(q));
+        crate::add_line(p, q, &mut self.space)
+    }
+
     pub fn draw_space(&mut self) {
-        crate::space::draw_space(&mut self.space, &mut self.screen);
+        crate::space::draw_space(&self.space, &mut self.screen);
+        if let Some(mesh) = &mut self.export {
+            mesh.add_space(&self.space);
+        }
+    }
+
+    ///keeps the triangles of everything drawn after this instead of throwing them away once they
+    ///are on the screen, so the scene can be saved as a mesh
+    pub fn start_export(&mut self) {
+        self.export = Some(Mesh::default());
+    }
+
+    ///the triangles kept since `start_export`, None if it wasn't called
+    pub fn export(&mut self) -> Option<&mut Mesh> {
+        self.export.as_mut()
+    }
+
+    ///stops keeping triangles and gives back the ones kept so far
+    pub fn take_export(&mut self) -> Option<Mesh> {
+        self.export.take()
     }
 
     pub fn clear_lines(&mut self) {
@@ -94,13 +235,33 @@ impl<T: Color> Engine<T> {
     }
 
     pub fn apply_sys(&mut self) {
-        self.space.apply(&mut self.stack.last().unwrap());
+        self.space.apply(self.stack.last().unwrap());
     }
 
     pub fn ppm_byte_vec(&self) -> Vec<u8> {
         self.screen.byte_vec()
     }
 
+    ///the screen as a frame of an animation
+    pub fn gif_frame(&self) -> gif::Frame {
+        gif::Frame::encode(
+            self.screen.width(),
+            self.screen.height(),
+            &self.screen.rgb_vec(),
+            self.gif.dither,
+        )
+    }
+
+    ///an animation with no frames yet, the size of the screen
+    pub fn gif(&self) -> Gif {
+        Gif::new(self.screen.width(), self.screen.height(), self.gif)
+    }
+
+    ///how the animations of scripts are played
+    pub fn set_gif_options(&mut self, options: GifOptions) {
+        self.gif = options;
+    }
+
     pub fn write_binary_ppm(&self, file: &mut File) {
         self.screen
             .write_binary_ppm(file)
@@ -111,14 +272,62 @@ impl<T: Color> Engine<T> {
         self.space.add_light(light);
     }
 
+    pub fn clear_lights(&mut self) {
+        self.space.clear_lights();
+    }
+
     pub fn set_camera(&mut self, p: (f64, f64, f64)) {
         self.space.set_camera(p);
     }
 
+    ///looks at `aim` from `eye`, the aim ends up in the middle of the screen
+    ///culling and the specular term then use the direction from `aim` to `eye`
+    pub fn look_at(&mut self, eye: (f64, f64, f64), aim: (f64, f64, f64)) {
+        let mut view = crate::move_matrix!(
+            self.screen.width() as f64 / 2.0,
+            self.screen.height() as f64 / 2.0,
+            0.0
+        );
+        Modtrix::multr(&mut view, &Modtrix::look_at(eye, aim));
+        self.space.set_view(view);
+        self.space
+            .set_camera(crate::gmath::normalize(crate::gmath::sub(eye, aim)));
+        self.space.set_eye(eye);
+        self.has_camera = true;
+    }
+
+    ///switches to a perspective projection where anything `focal` away from the eye is drawn at
+    ///its own size, without a camera the eye is `focal` in front of the middle of the screen
+    pub fn set_focal(&mut self, focal: f64) {
+        self.space.set_projection(Projection::Perspective(focal));
+        if !self.has_camera {
+            self.space.set_eye((
+                self.screen.width() as f64 / 2.0,
+                self.screen.height() as f64 / 2.0,
+                focal,
+            ));
+        }
+    }
+
+    ///anything closer to the eye than `near` or further than `far` is not drawn
+    ///this only matters with perspective
+    pub fn set_depth_range(&mut self, near: f64, far: f64) {
+        self.space.set_depth_range(near, far);
+    }
+
+    ///how the shapes drawn after this are colored in
+    pub fn set_shading(&mut self, shading: Shading) {
+        self.space.set_shading(shading);
+    }
+
     pub fn set_ambient_light(&mut self, color: T) {
         self.space.set_ambient_light(color);
     }
 
+    ///gives a knob the values from `v0` to `v1` in the frames from `start_frame` to `end_frame`,
+    ///spaced out by `easing`, where ranges overlap the one added last wins
+    ///fails without changing anything if the range is backwards or doesn't fit in `total_frames`
+    #[allow(clippy::too_many_arguments)]
     pub fn add_vary(
         &mut self,
         name: String,
@@ -126,22 +335,112 @@ impl<T: Color> Engine<T> {
         end_frame: u32,
         v0: f64,
         v1: f64,
+        easing: Easing,
         total_frames: u32,
-    ) {
-        //currently assume transformation totally done if outside specified vary ammount
-        //this should mean the indexing following won't fail
+    ) -> Result<(), KnobError> {
+        check_range(start_frame, end_frame, total_frames)?;
         let frames = self
             .frames
             .entry(name)
-            .or_insert(vec![1.0; total_frames as usize + 1]);
+            .or_insert(vec![None; total_frames as usize]);
         let df = (end_frame - start_frame) as f64;
         for (i, f) in (start_frame..=end_frame).enumerate() {
-            frames[f as usize] = v0 + i as f64 * (v1 - v0) / df;
+            //a range of one frame jumps straight to the end
+            let t = if df == 0.0 { 1.0 } else { i as f64 / df };
+            frames[f as usize] = Some(v0 + easing.apply(t) * (v1 - v0));
         }
+        Ok(())
+    }
+
+    ///gives a knob the values of a spline in every frame unless it has a `vary` or `tween`, a
+    ///spline added again for the same knob replaces the old one, fails if the spline has a
+    ///`problem`
+    pub fn add_spline(&mut self, name: String, spline: Spline) -> Result<(), KnobError> {
+        if let Some(problem) = spline.problem() {
+            return Err(KnobError::Spline(problem));
+        }
+        self.splines.insert(name, spline);
+        Ok(())
+    }
+
+    ///makes a knob known to the engine, at 1 unless it already has a value, so `set_knobs` and
+    ///`save_knobs` include it
+    pub fn add_knob(&mut self, name: &str) {
+        self.knobs.entry(name.to_string()).or_insert(1.0);
+    }
+
+    pub fn set_knob(&mut self, name: &str, value: f64) {
+        self.knobs.insert(name.to_string(), value);
+    }
+
+    ///sets every knob the engine knows about
+    pub fn set_knobs(&mut self, value: f64) {
+        self.knobs.values_mut().for_each(|v| *v = value);
     }
 
+    ///saves the value every knob has now so they can be tweened from or to, a name saved again is
+    ///replaced
+    pub fn save_knobs(&mut self, list: &str) {
+        self.knob_lists.insert(list.to_string(), self.knobs.clone());
+    }
+
+    ///moves every knob in two saved lists from its value in the first to its value in the second
+    ///over the frames from `start_frame` to `end_frame`, a knob only one list has keeps that value
+    ///fails without changing anything if a list was never saved or the range is wrong like in
+    ///`add_vary`
+    pub fn add_tween(
+        &mut self,
+        start_frame: u32,
+        end_frame: u32,
+        list0: &str,
+        list1: &str,
+        total_frames: u32,
+    ) -> Result<(), KnobError> {
+        let list = |name: &str| {
+            self.knob_lists
+                .get(name)
+                .cloned()
+                .ok_or_else(|| KnobError::UnknownList(name.to_string()))
+        };
+        let (list0, list1) = (list(list0)?, list(list1)?);
+        check_range(start_frame, end_frame, total_frames)?;
+        let mut names: Vec<_> = list0.keys().chain(list1.keys()).collect();
+        names.sort();
+        names.dedup();
+        for name in names {
+            let v0 = list0.get(name).or_else(|| list1.get(name)).unwrap();
+            let v1 = list1.get(name).unwrap_or(v0);
+            self.add_vary(
+                name.clone(),
+                start_frame,
+                end_frame,
+                *v0,
+                *v1,
+                Easing::Linear,
+                total_frames,
+            )?;
+        }
+        Ok(())
+    }
+
+    ///the value of a knob in a frame, from `vary` or `tween` if it has any, then its spline if it has
+    ///one, otherwise what it was set to, knobs the engine has never heard of are 1
+    ///between the ranges of `vary` and `tween` a knob holds the value the range before left it at,
+    ///and before the first range it has the value the first starts at
     pub fn get_knob_val(&self, name: String, frame: u32) -> f64 {
-        self.frames.get(&name).unwrap()[frame as usize]
+        self.frames
+            .get(&name)
+            .and_then(|frames| {
+                let now = (frame as usize).min(frames.len().saturating_sub(1));
+                frames[..=now]
+                    .iter()
+                    .rev()
+                    .chain(&frames[now..])
+                    .find_map(|v| *v)
+            })
+            .or_else(|| self.splines.get(&name).map(|s| s.value(frame as f64)))
+            .or_else(|| self.knobs.get(&name).copied())
+            .unwrap_or(1.0)
     }
 
     pub fn clear_screen(&mut self) {
@@ -152,3 +451,45 @@ impl<T: Color> Engine<T> {
         self.stack = vec![Modtrix::IDENT];
     }
 }
+
+///why a knob couldn't be animated
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum KnobError {
+    ///the frames from `start` to `end` are backwards or go past the last of `total`
+    FrameRange { start: u32, end: u32, total: u32 },
+    ///a list of knobs tweened from or to was never saved
+    UnknownList(String),
+    ///the keys of a spline can't be made into a curve, for the reason given
+    Spline(String),
+}
+
+impl fmt::Display for KnobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FrameRange { start, end, .. } if end < start => {
+                write!(
+                    f,
+                    "ends at frame {} before it starts at frame {}",
+                    end, start
+                )
+            }
+            Self::FrameRange { end, total, .. } => write!(
+                f,
+                "ends at frame {} but the animation has {} frames",
+                end, total
+            ),
+            Self::UnknownList(name) => write!(f, "knob list `{}` was never saved", name),
+            Self::Spline(problem) => write!(f, "{}", problem),
+        }
+    }
+}
+
+impl Error for KnobError {}
+
+fn check_range(start: u32, end: u32, total: u32) -> Result<(), KnobError> {
+    if start <= end && end < total {
+        Ok(())
+    } else {
+        Err(KnobError::FrameRange { start, end, total })
+    }
+}
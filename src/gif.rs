@@ -0,0 +1,379 @@
+//!writing animations as GIF89a files without any other programs, each frame gets its own palette
+//!of at most 256 colors picked by median cut
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+///how an animation is played
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GifOptions {
+    ///how long each frame is shown, in hundredths of a second
+    pub delay: u16,
+    ///how many times the animation plays, 0 plays it forever
+    pub loops: u16,
+    ///spread the error of frames with more than 256 colors onto the pixels around it, so smooth
+    ///shading is speckled instead of banded
+    pub dither: bool,
+}
+
+impl Default for GifOptions {
+    ///forever at 50 frames a second, the closest a gif gets to the 1.7 hundredths convert was
+    ///given, browsers slow down anything quicker
+    fn default() -> Self {
+        Self {
+            delay: 2,
+            loops: 0,
+            dither: false,
+        }
+    }
+}
+
+///one picture of an animation, quantized and compressed so it only needs writing
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    ///always a power of 2 long, at least 2
+    palette: Vec<[u8; 3]>,
+    min_code_size: u8,
+    ///the LZW codes packed into bytes, not yet split into blocks
+    data: Vec<u8>,
+}
+
+impl Frame {
+    ///a frame of `rgb`, 3 bytes a pixel with the top row first, colors are only lost if there
+    ///are more than 256 of them
+    pub fn encode(width: usize, height: usize, rgb: &[u8], dither: bool) -> Self {
+        assert_eq!(
+            rgb.len(),
+            width * height * 3,
+            "the pixels don't fill a {}x{} frame",
+            width,
+            height
+        );
+        let pixels: Vec<[u8; 3]> = rgb.chunks_exact(3).map(|p| [p[0], p[1], p[2]]).collect();
+        let mut palette = median_cut(&pixels);
+        let indices = if dither {
+            dithered(&pixels, width, &palette)
+        } else {
+            let mut nearest = Nearest::new(&palette);
+            pixels.iter().map(|&p| nearest.find(p)).collect()
+        };
+        let bits = (1..=8).find(|&b| palette.len() <= 1 << b).unwrap_or(8);
+        palette.resize(1 << bits, [0; 3]);
+        let min_code_size = bits.max(2);
+        Self {
+            width,
+            height,
+            palette,
+            min_code_size,
+            data: lzw(&indices, min_code_size),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+}
+
+///an animation, frames are pushed in the order they are played
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Gif {
+    width: usize,
+    height: usize,
+    options: GifOptions,
+    frames: Vec<Frame>,
+}
+
+impl Gif {
+    pub fn new(width: usize, height: usize, options: GifOptions) -> Self {
+        Self {
+            width,
+            height,
+            options,
+            frames: Vec::new(),
+        }
+    }
+
+    ///adds a frame to the end, it must be the size of the animation
+    pub fn push(&mut self, frame: Frame) {
+        assert_eq!(
+            (frame.width, frame.height),
+            (self.width, self.height),
+            "a frame must be the size of its animation"
+        );
+        self.frames.push(frame);
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    ///writes the whole file, a gif can't be more than 65535 pixels across or down
+    pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        let dim = |d: usize| {
+            u16::try_from(d).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("a gif can't be {} pixels across or down", d),
+                )
+            })
+        };
+        let (width, height) = (dim(self.width)?, dim(self.height)?);
+
+        out.write_all(b"GIF89a")?;
+        //no global palette, every frame brings its own, with 8 bits for each of red, green, blue
+        out.write_all(&width.to_le_bytes())?;
+        out.write_all(&height.to_le_bytes())?;
+        out.write_all(&[0x70, 0, 0])?;
+        //the netscape extension says how many times to play again after the first, playing
+        //once is what happens without it
+        if self.options.loops != 1 {
+            out.write_all(&[0x21, 0xff, 11])?;
+            out.write_all(b"NETSCAPE2.0")?;
+            out.write_all(&[3, 1])?;
+            out.write_all(&self.options.loops.saturating_sub(1).to_le_bytes())?;
+            out.write_all(&[0])?;
+        }
+        for frame in &self.frames {
+            //graphic control, each frame covers the last completely so it is left in place
+            out.write_all(&[0x21, 0xf9, 4, 1 << 2])?;
+            out.write_all(&self.options.delay.to_le_bytes())?;
+            out.write_all(&[0, 0])?;
+            //image descriptor, the whole screen with a local palette
+            out.write_all(&[0x2c, 0, 0, 0, 0])?;
+            out.write_all(&width.to_le_bytes())?;
+            out.write_all(&height.to_le_bytes())?;
+            let table_size = frame.palette.len().trailing_zeros() as u8 - 1;
+            out.write_all(&[0x80 | table_size])?;
+            for color in &frame.palette {
+                out.write_all(color)?;
+            }
+            out.write_all(&[frame.min_code_size])?;
+            for block in frame.data.chunks(255) {
+                out.write_all(&[block.len() as u8])?;
+                out.write_all(block)?;
+            }
+            out.write_all(&[0])?;
+        }
+        out.write_all(&[0x3b])?;
+        Ok(())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        self.write(&mut file)?;
+        file.flush()
+    }
+}
+
+///at most 256 colors standing in for the pixels, the colors themselves if there are few enough,
+///otherwise the pixels are split in two along the color they vary most in, again and again,
+///and each group is its average
+fn median_cut(pixels: &[[u8; 3]]) -> Vec<[u8; 3]> {
+    let mut counts = HashMap::new();
+    for &p in pixels {
+        *counts.entry(p).or_insert(0u64) += 1;
+    }
+    let mut colors: Vec<_> = counts.into_iter().collect();
+    //sorted so the same frame always gets the same palette
+    colors.sort_unstable();
+    if colors.len() <= 256 {
+        return colors.into_iter().map(|(c, _)| c).collect();
+    }
+
+    let widest = |group: &[([u8; 3], u64)]| {
+        (0..3)
+            .map(|ch| {
+                let (lo, hi) = group.iter().fold((u8::MAX, 0), |(lo, hi), (c, _)| {
+                    (lo.min(c[ch]), hi.max(c[ch]))
+                });
+                (hi - lo, ch)
+            })
+            .max()
+            .unwrap()
+    };
+    let mut groups = vec![colors];
+    while groups.len() < 256 {
+        //the group spread out the most is split next
+        let next = groups
+            .iter()
+            .enumerate()
+            .filter(|(_, g)| g.len() > 1)
+            .map(|(i, g)| (widest(g), i))
+            .max_by_key(|((range, _), i)| (*range, std::cmp::Reverse(*i)));
+        let ((_, ch), i) = match next {
+            Some(n) => n,
+            None => break,
+        };
+        let mut group = groups.swap_remove(i);
+        group.sort_unstable_by_key(|(c, _)| c[ch]);
+        //split where half the pixels are on each side, leaving at least one color in each
+        let total: u64 = group.iter().map(|(_, n)| n).sum();
+        let mut seen = 0;
+        let mid = group
+            .iter()
+            .position(|(_, n)| {
+                seen += n;
+                seen * 2 >= total
+            })
+            .unwrap()
+            .min(group.len() - 2)
+            + 1;
+        let rest = group.split_off(mid);
+        groups.push(group);
+        groups.push(rest);
+    }
+
+    groups
+        .iter()
+        .map(|g| {
+            let total: u64 = g.iter().map(|(_, n)| n).sum();
+            let mut avg = [0; 3];
+            for (ch, a) in avg.iter_mut().enumerate() {
+                let sum: u64 = g.iter().map(|(c, n)| c[ch] as u64 * n).sum();
+                *a = ((sum + total / 2) / total) as u8;
+            }
+            avg
+        })
+        .collect()
+}
+
+///finds the closest color in a palette, remembering the answer for each color asked about
+struct Nearest<'a> {
+    palette: &'a [[u8; 3]],
+    found: HashMap<[u8; 3], u8>,
+}
+
+impl<'a> Nearest<'a> {
+    fn new(palette: &'a [[u8; 3]]) -> Self {
+        Self {
+            palette,
+            found: HashMap::new(),
+        }
+    }
+
+    fn find(&mut self, color: [u8; 3]) -> u8 {
+        let palette = self.palette;
+        *self.found.entry(color).or_insert_with(|| {
+            let dist = |p: &[u8; 3]| {
+                (0..3)
+                    .map(|ch| (p[ch] as i32 - color[ch] as i32).pow(2))
+                    .sum::<i32>()
+            };
+            (0..palette.len())
+                .min_by_key(|&i| dist(&palette[i]))
+                .unwrap() as u8
+        })
+    }
+}
+
+///the palette index of each pixel with Floyd-Steinberg dithering, the difference between a pixel
+///and the color it gets is passed on to the pixels right of and below it
+fn dithered(pixels: &[[u8; 3]], width: usize, palette: &[[u8; 3]]) -> Vec<u8> {
+    let mut nearest = Nearest::new(palette);
+    //sixteenths of a color value carried to each pixel
+    let mut error = vec![[0i32; 3]; pixels.len()];
+    let mut out = Vec::with_capacity(pixels.len());
+    for (i, p) in pixels.iter().enumerate() {
+        let mut color = [0; 3];
+        for ch in 0..3 {
+            color[ch] = (p[ch] as i32 + error[i][ch] / 16).clamp(0, 255) as u8;
+        }
+        let index = nearest.find(color);
+        out.push(index);
+        let (x, below) = (i % width, i + width);
+        let mut spread = |j: usize, share: i32| {
+            if let Some(e) = error.get_mut(j) {
+                for ch in 0..3 {
+                    e[ch] += (color[ch] as i32 - palette[index as usize][ch] as i32) * share;
+                }
+            }
+        };
+        if x + 1 < width {
+            spread(i + 1, 7);
+            spread(below + 1, 1);
+        }
+        if x > 0 {
+            spread(below - 1, 3);
+        }
+        spread(below, 5);
+    }
+    out
+}
+
+///the variable width LZW gifs use, codes start one bit wider than `min_code_size` and grow as
+///the table does up to 12 bits, then the table is cleared and started again
+fn lzw(indices: &[u8], min_code_size: u8) -> Vec<u8> {
+    const MAX_CODES: u16 = 1 << 12;
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut out = Bits::default();
+    let mut size = min_code_size + 1;
+    let mut next = end + 1;
+    let mut table = HashMap::new();
+
+    out.push(clear, size);
+    let mut indices = indices.iter();
+    if let Some(&first) = indices.next() {
+        let mut prefix = first as u16;
+        for &k in indices {
+            if let Some(&code) = table.get(&(prefix, k)) {
+                prefix = code;
+                continue;
+            }
+            out.push(prefix, size);
+            if next < MAX_CODES {
+                table.insert((prefix, k), next);
+                next += 1;
+                //the reader adds each code a step later, so it only needs the wider codes once
+                //the code after this one could be read
+                if next > 1 << size {
+                    size += 1;
+                }
+            } else {
+                out.push(clear, size);
+                table.clear();
+                size = min_code_size + 1;
+                next = end + 1;
+            }
+            prefix = k as u16;
+        }
+        out.push(prefix, size);
+    }
+    out.push(end, size);
+    out.finish()
+}
+
+///codes packed into bytes from the lowest bit up
+#[derive(Default)]
+struct Bits {
+    bytes: Vec<u8>,
+    pending: u32,
+    count: u8,
+}
+
+impl Bits {
+    fn push(&mut self, code: u16, size: u8) {
+        self.pending |= (code as u32) << self.count;
+        self.count += size;
+        while self.count >= 8 {
+            self.bytes.push(self.pending as u8);
+            self.pending >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.pending as u8);
+        }
+        self.bytes
+    }
+}
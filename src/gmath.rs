@@ -17,6 +17,14 @@ pub fn norm(p1: Point, p2: Point, p3: Point) -> Point {
     )
 }
 
+pub fn cross(v1: Point, v2: Point) -> Point {
+    (
+        v1.1 * v2.2 - v1.2 * v2.1,
+        v1.2 * v2.0 - v1.0 * v2.2,
+        v1.0 * v2.1 - v1.1 * v2.0,
+    )
+}
+
 pub fn normalize(p: Point) -> Point {
     let rat = 1.0 / (p.0 * p.0 + p.1 * p.1 + p.2 * p.2).sqrt();
     (p.0 * rat, p.1 * rat, p.2 * rat)
@@ -1,14 +1,24 @@
+pub mod diagnostic;
 pub mod draw;
+pub mod easing;
+pub mod gif;
 pub mod gmath;
+pub mod mesh;
 pub mod screen;
 pub mod space;
+pub mod spline;
 
 mod engine;
 mod parser;
 
+pub use diagnostic::{Code, Diagnostic, Diagnostics, Severity, Span};
 pub use draw::*;
+pub use easing::Easing;
 pub use engine::*;
+pub use gif::{Gif, GifOptions};
 pub use gmath::*;
+pub use mesh::{Mesh, MeshError};
 pub use parser::*;
 pub use screen::{Color, RGB8Color, Screen};
-pub use space::{Float, Light, Modtrix, Space};
+pub use space::{Float, Light, Modtrix, Projection, Shading, Space};
+pub use spline::Spline;
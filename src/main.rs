@@ -9,6 +9,20 @@
 //!the script file. The commands are the format specified src/parser/MDL.spec
 //!
 //!Currently not all of MDL is supported.
+//!
+//!Running with `--disassemble <file>` prints the commands of a script or intermediate file, one
+//!per line with the byte offset of each command in the intermediate format, instead of running it.
+//!
+//!Running with `--resolution <steps|auto|tolerance px>` sets how finely round shapes are drawn
+//!when the script doesn't say, a low number makes quick drafts.
+//!
+//!Running with `--export <file.obj|file.stl>` also saves the triangles of everything the script
+//!draws to a mesh file, for looking at in other programs or printing. Only the first frame of an
+//!animation is saved.
+//!
+//!Animations are saved as gifs. Running with `--delay <hundredths>` sets how long each frame is
+//!shown, `--loops <count>` how many times it plays with 0 for forever, and `--dither` speckles
+//!frames with more than 256 colors instead of banding them.
 
 #![warn(missing_docs, missing_debug_implementations, rust_2018_idioms)]
 #![allow(elided_lifetimes_in_paths)]
@@ -16,46 +30,141 @@
 use std::env;
 use std::error::Error;
 use std::fs;
-use std::process::{self, Command};
-
-use binrw::io::Cursor;
-use binrw::BinRead;
+use std::process;
 
 ///default width of an image
 pub const IMAGE_WIDTH: usize = 500;
 ///default height of an image
 pub const IMAGE_HEIGHT: usize = 500;
 
-use graphics::{Engine, Light, RGB8Color, Script};
+use graphics::{
+    Diagnostics, Engine, GifOptions, Light, RGB8Color, Resolution, Script, MIN_RESOLUTION,
+};
 
-fn run(script: Script) -> Result<(), Box<dyn Error>> {
+fn run(
+    script: Script,
+    diag: &mut Diagnostics,
+    resolution: Resolution,
+    export: Option<String>,
+    gif: GifOptions,
+) -> Result<(), Box<dyn Error>> {
     let mut eng = Engine::<RGB8Color>::with_screen_dims(IMAGE_WIDTH, IMAGE_HEIGHT);
+    //resolution commands in the script still win
+    eng.set_resolution(resolution);
+    eng.set_gif_options(gif);
+    //the script's own ambient and lights replace these
     eng.set_ambient_light((50, 50, 50).into());
     //let light = Light::new((0.5, 0.75, 1.0), (0, 255, 255).into());
     let light = Light::new((5000.0, 7500.0, 10000.0), (255, 255, 255).into());
     eng.add_light(light);
     eng.set_camera((0.0, 0.0, 1.0));
 
-    script.exec(&mut eng);
+    if export.is_some() {
+        eng.start_export();
+    }
+
+    script.exec(&mut eng, diag);
+    if diag.has_errors() {
+        return Ok(());
+    }
 
+    if let (Some(file), Some(mesh)) = (export, eng.take_export()) {
+        mesh.save(&file)
+            .map_err(|e| format!("cannot export to {}: {}", file, e))?;
+    }
     Ok(())
 }
 
+fn parse_resolution(args: &mut impl Iterator<Item = String>) -> Result<Resolution, Box<dyn Error>> {
+    let arg = args
+        .next()
+        .ok_or("--resolution needs a number of steps, auto, or tolerance")?;
+    if arg == "auto" {
+        return Ok(Resolution::Auto);
+    }
+    if arg == "tolerance" {
+        let px = args.next().unwrap_or_default();
+        return match px.parse::<f64>() {
+            Ok(t) if t > 0.0 => Ok(Resolution::Tolerance(t)),
+            _ => Err(format!(
+                "--resolution tolerance needs a number of pixels more than 0, not {:?}",
+                px
+            )
+            .into()),
+        };
+    }
+    match arg.parse::<usize>() {
+        Ok(n) if n >= MIN_RESOLUTION => Ok(Resolution::Steps(n)),
+        _ => Err(format!(
+            "--resolution needs a number of steps of at least {}, auto, or tolerance, not {}",
+            MIN_RESOLUTION, arg
+        )
+        .into()),
+    }
+}
+
+fn parse_u16(flag: &str, what: &str, arg: Option<String>) -> Result<u16, Box<dyn Error>> {
+    let arg = arg.unwrap_or_default();
+    arg.parse()
+        .map_err(|_| format!("{} needs {} from 0 to 65535, not {:?}", flag, what, arg).into())
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
-    let args: Vec<String> = env::args().collect();
-    Command::new("./deps/mdl")
-        .arg(args.get(1).ok_or("No Input File Given")?)
-        .status()
-        .expect("failed to create intermediate with dw mdl parser");
-    let script = fs::read("a.mdl_intermediate_language")?;
-    let mut input = Cursor::new(script);
-    let s = Script::read(&mut input).expect("could not read intermediate file");
-
-    match run(s) {
+    let mut disassemble = false;
+    let mut resolution = Resolution::default();
+    let mut export = None;
+    let mut gif = GifOptions::default();
+    let mut path = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            //prints the commands instead of running them
+            "--disassemble" => disassemble = true,
+            "--resolution" => resolution = parse_resolution(&mut args)?,
+            "--export" => export = Some(args.next().ok_or("--export needs a file to save to")?),
+            "--delay" => gif.delay = parse_u16(&arg, "hundredths of a second", args.next())?,
+            "--loops" => gif.loops = parse_u16(&arg, "a number of times", args.next())?,
+            "--dither" => gif.dither = true,
+            _ => path = Some(arg),
+        }
+    }
+    let path = &path.ok_or("No Input File Given")?;
+    //problems found while running are added to the ones found while parsing, so they are shown
+    //in the source too
+    let (s, mut diag) = if path.ends_with(".mdl_intermediate_language") {
+        match Script::from_bytes(&fs::read(path)?) {
+            Ok(s) => (s, Diagnostics::with_source(path, "")),
+            Err(e) => {
+                eprintln!("{}: {}", path, e);
+                process::exit(1);
+            }
+        }
+    } else {
+        let src = fs::read_to_string(path)?;
+        let mut diag = Diagnostics::with_source(path, &src);
+        match Script::from_mdl(&src, &mut diag) {
+            None => {
+                eprint!("{}", diag);
+                process::exit(1);
+            }
+            Some(s) => (s, diag),
+        }
+    };
+
+    if disassemble {
+        eprint!("{}", diag);
+        print!("{}", s.disassemble());
+        process::exit(0);
+    }
+
+    let result = run(s, &mut diag, resolution, export, gif);
+    eprint!("{}", diag);
+    match result {
         Err(e) => {
             eprintln!("{}", e);
             process::exit(1);
         }
+        Ok(_) if diag.has_errors() => process::exit(1),
         Ok(_) => {
             process::exit(0);
         }
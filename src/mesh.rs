@@ -0,0 +1,203 @@
+//!meshes of triangles loaded from files made by other programs, so scripts can draw more than the
+//!shapes MDL has built in
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::gmath;
+use crate::screen::Color;
+use crate::space::{Float, Point, Space};
+
+mod obj;
+mod ply;
+mod stl;
+
+///one corner of a face, indices into the lists of the mesh
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Corner {
+    pub position: usize,
+    pub uv: Option<usize>,
+    pub normal: Option<usize>,
+}
+
+///a mesh of triangles, the corners of each are counter clockwise when looking at its front
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Mesh {
+    pub positions: Vec<Point>,
+    pub normals: Vec<Point>,
+    ///texture coordinates, kept so nothing in the file is lost though nothing draws them yet
+    pub uvs: Vec<(Float, Float)>,
+    pub faces: Vec<[Corner; 3]>,
+}
+
+///why a mesh couldn't be loaded
+#[derive(Debug)]
+pub enum MeshError {
+    Io(io::Error),
+    ///a binary file that couldn't be read, the error says at which byte
+    Binary(binrw::Error),
+    ///the file could be read but not understood, `line` starts at 1
+    Parse {
+        line: usize,
+        message: String,
+    },
+    UnknownFormat(String),
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Binary(e) => write!(f, "{}", e),
+            Self::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            Self::UnknownFormat(ext) => {
+                write!(f, "`{}` is not a known mesh format", ext)
+            }
+        }
+    }
+}
+
+impl Error for MeshError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Binary(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MeshError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<binrw::Error> for MeshError {
+    fn from(e: binrw::Error) -> Self {
+        match e {
+            binrw::Error::Io(e) => Self::Io(e),
+            e => Self::Binary(e),
+        }
+    }
+}
+
+///the extension of a file in lower case, or nothing if it has none
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+fn parse_error<T>(line: usize, message: String) -> Result<T, MeshError> {
+    Err(MeshError::Parse { line, message })
+}
+
+impl Mesh {
+    ///reads a mesh from a file, the format is picked from the extension
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, MeshError> {
+        let path = path.as_ref();
+        let ext = extension(path);
+        match ext.as_str() {
+            "obj" => Self::from_obj(&fs::read_to_string(path)?),
+            "stl" => Self::from_stl(&fs::read(path)?),
+            "ply" => Self::from_ply(&fs::read(path)?),
+            _ => Err(MeshError::UnknownFormat(ext)),
+        }
+    }
+
+    ///writes the mesh to a file, the format is picked from the extension
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), MeshError> {
+        let path = path.as_ref();
+        match extension(path).as_str() {
+            "obj" => fs::write(path, self.to_obj())?,
+            "stl" => fs::write(path, self.to_stl())?,
+            ext => return Err(MeshError::UnknownFormat(ext.into())),
+        }
+        Ok(())
+    }
+
+    ///reads a mesh from the text of a Wavefront OBJ file
+    pub fn from_obj(src: &str) -> Result<Self, MeshError> {
+        obj::parse(src)
+    }
+
+    ///reads a mesh from an STL file, binary or text, corners at the same spot are joined
+    pub fn from_stl(bytes: &[u8]) -> Result<Self, MeshError> {
+        stl::parse(bytes)
+    }
+
+    ///reads a mesh from a PLY file, text or binary, the `vertex` and `face` elements are used
+    pub fn from_ply(bytes: &[u8]) -> Result<Self, MeshError> {
+        ply::parse(bytes)
+    }
+
+    ///the text of an OBJ file with the mesh in it
+    pub fn to_obj(&self) -> String {
+        obj::write(self)
+    }
+
+    ///a binary STL file with the mesh in it, STL only has positions so everything else is lost
+    pub fn to_stl(&self) -> Vec<u8> {
+        stl::write(self)
+    }
+
+    ///adds the triangles of a space to the mesh, as they are after any `Space::apply`
+    ///normals are normalized since transforming them can change their length
+    pub fn add_space<T: Color>(&mut self, space: &Space<T>) {
+        let mut added: HashMap<u32, Corner> = HashMap::new();
+        for tri in space.indexed_triangles() {
+            let face = tri.map(|i| {
+                *added.entry(i).or_insert_with(|| {
+                    let (p, nrm) = space.vertex(i);
+                    self.positions.push(p);
+                    Corner {
+                        position: self.positions.len() - 1,
+                        uv: None,
+                        normal: nrm.map(|n| {
+                            self.normals.push(gmath::normalize(n));
+                            self.normals.len() - 1
+                        }),
+                    }
+                })
+            });
+            self.faces.push(face);
+        }
+    }
+
+    ///adds every face of another mesh to this one
+    pub fn append(&mut self, other: &Mesh) {
+        let (p, t, n) = (self.positions.len(), self.uvs.len(), self.normals.len());
+        self.positions.extend_from_slice(&other.positions);
+        self.uvs.extend_from_slice(&other.uvs);
+        self.normals.extend_from_slice(&other.normals);
+        self.faces.extend(other.faces.iter().map(|f| {
+            f.map(|c| Corner {
+                position: c.position + p,
+                uv: c.uv.map(|i| i + t),
+                normal: c.normal.map(|i| i + n),
+            })
+        }));
+    }
+
+    ///adds every face to the space, corners which share a position and normal share a vertex
+    pub fn add_to<T: Color>(&self, space: &mut Space<T>) {
+        let mut added: HashMap<(usize, Option<usize>), u32> = HashMap::new();
+        for face in &self.faces {
+            let [a, b, c] = face.map(|corner| {
+                *added
+                    .entry((corner.position, corner.normal))
+                    .or_insert_with(|| {
+                        let nrm = corner.normal.map(|n| self.normals[n]);
+                        space.add_vertex(self.positions[corner.position], nrm)
+                    })
+            });
+            space.add_indexed_tri(a, b, c);
+        }
+    }
+}
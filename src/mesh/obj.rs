@@ -0,0 +1,151 @@
+//!Wavefront OBJ, only the geometry is read, materials, groups, and smoothing are skipped
+
+use std::fmt::Write;
+
+use super::{parse_error as error, Corner, Mesh, MeshError};
+use crate::space::{Float, Point};
+
+fn numbers<'a>(
+    line: usize,
+    what: &str,
+    words: impl Iterator<Item = &'a str>,
+) -> Result<Vec<Float>, MeshError> {
+    words
+        .map(|w| match w.parse() {
+            Ok(n) => Ok(n),
+            Err(_) => error(line, format!("`{}` in {} is not a number", w, what)),
+        })
+        .collect()
+}
+
+fn point(line: usize, what: &str, n: &[Float]) -> Result<Point, MeshError> {
+    match *n {
+        [x, y, z] => Ok((x, y, z)),
+        //the optional w of a position, points at infinity can't be drawn anyway
+        [x, y, z, w] if w != 0.0 => Ok((x / w, y / w, z / w)),
+        _ => error(line, format!("{} needs 3 numbers, found {}", what, n.len())),
+    }
+}
+
+///turns an index from the file into one into a list which has `len` things so far
+///indices start at 1 and negative ones count back from the end of the list
+fn index(line: usize, what: &str, word: &str, len: usize) -> Result<usize, MeshError> {
+    let i: i64 = match word.parse() {
+        Ok(i) => i,
+        Err(_) => return error(line, format!("`{}` is not a {} index", word, what)),
+    };
+    let found = if i > 0 {
+        i as usize - 1
+    } else {
+        //a negative index past the start wraps so it is caught by the check below
+        (len as i64 + i) as usize
+    };
+    if i == 0 || found >= len {
+        return error(
+            line,
+            format!("{} index {} is out of range, there are {}", what, i, len),
+        );
+    }
+    Ok(found)
+}
+
+///one corner of a face, `v`, `v/vt`, `v//vn`, or `v/vt/vn`
+fn corner(line: usize, word: &str, mesh: &Mesh) -> Result<Corner, MeshError> {
+    let parts: Vec<_> = word.split('/').collect();
+    if parts.len() > 3 {
+        return error(line, format!("`{}` has too many parts for a corner", word));
+    }
+    let position = index(line, "position", parts[0], mesh.positions.len())?;
+    let uv = match parts.get(1) {
+        None | Some(&"") => None,
+        Some(w) => Some(index(line, "texture coordinate", w, mesh.uvs.len())?),
+    };
+    let normal = match parts.get(2) {
+        None | Some(&"") => None,
+        Some(w) => Some(index(line, "normal", w, mesh.normals.len())?),
+    };
+    Ok(Corner {
+        position,
+        uv,
+        normal,
+    })
+}
+
+pub(super) fn parse(src: &str) -> Result<Mesh, MeshError> {
+    let mut mesh = Mesh::default();
+    for (l, text) in src.lines().enumerate() {
+        let line = l + 1;
+        let text = text.split('#').next().unwrap();
+        let mut words = text.split_whitespace();
+        match words.next() {
+            Some("v") => {
+                let n = numbers(line, "a position", words)?;
+                mesh.positions.push(point(line, "a position", &n)?);
+            }
+            Some("vn") => {
+                let n = numbers(line, "a normal", words)?;
+                mesh.normals.push(point(line, "a normal", &n)?);
+            }
+            Some("vt") => match numbers(line, "a texture coordinate", words)?[..] {
+                [u] => mesh.uvs.push((u, 0.0)),
+                [u, v] | [u, v, _] => mesh.uvs.push((u, v)),
+                ref n => {
+                    return error(
+                        line,
+                        format!(
+                            "a texture coordinate needs 1 to 3 numbers, found {}",
+                            n.len()
+                        ),
+                    )
+                }
+            },
+            Some("f") => {
+                let corners = words
+                    .map(|w| corner(line, w, &mesh))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if corners.len() < 3 {
+                    return error(
+                        line,
+                        format!("a face needs at least 3 corners, found {}", corners.len()),
+                    );
+                }
+                //faces with more corners are assumed to be flat and convex, so a fan covers them
+                for i in 1..corners.len() - 1 {
+                    mesh.faces.push([corners[0], corners[i], corners[i + 1]]);
+                }
+            }
+            //everything else is about materials, grouping, or shapes other than triangles
+            _ => (),
+        }
+    }
+    Ok(mesh)
+}
+
+///the text of an OBJ file with the mesh in it, texture coordinates are written if there are any
+pub(super) fn write(mesh: &Mesh) -> String {
+    //writing to a string can't fail
+    let mut out = String::from("# exported from MDL\n");
+    for p in &mesh.positions {
+        writeln!(out, "v {} {} {}", p.0, p.1, p.2).unwrap();
+    }
+    for (u, v) in &mesh.uvs {
+        writeln!(out, "vt {} {}", u, v).unwrap();
+    }
+    for n in &mesh.normals {
+        writeln!(out, "vn {} {} {}", n.0, n.1, n.2).unwrap();
+    }
+    for face in &mesh.faces {
+        out.push('f');
+        for c in face {
+            write!(out, " {}", c.position + 1).unwrap();
+            match (c.uv, c.normal) {
+                (None, None) => (),
+                (Some(t), None) => write!(out, "/{}", t + 1).unwrap(),
+                (None, Some(n)) => write!(out, "//{}", n + 1).unwrap(),
+                (Some(t), Some(n)) => write!(out, "/{}/{}", t + 1, n + 1).unwrap(),
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,370 @@
+//!PLY, in text or either byte order, the vertex and face elements are read and any others skipped
+
+use binrw::io::Cursor;
+use binrw::{BinReaderExt, BinResult, Endian};
+
+use super::{parse_error, Corner, Mesh, MeshError};
+use crate::space::Float;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Type {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Type {
+    fn parse(word: &str) -> Option<Self> {
+        Some(match word {
+            "char" | "int8" => Self::I8,
+            "uchar" | "uint8" => Self::U8,
+            "short" | "int16" => Self::I16,
+            "ushort" | "uint16" => Self::U16,
+            "int" | "int32" => Self::I32,
+            "uint" | "uint32" => Self::U32,
+            "float" | "float32" => Self::F32,
+            "double" | "float64" => Self::F64,
+            _ => return None,
+        })
+    }
+}
+
+struct Property {
+    name: String,
+    ty: Type,
+    ///the type of the length in front of a list, None if the property is a single value
+    list: Option<Type>,
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+enum Format {
+    Text,
+    Binary(Endian),
+}
+
+///where the values of the elements come from, the body of the file after the header
+enum Body<'a> {
+    ///each item of an element is on a line of its own
+    Text {
+        lines: std::iter::Enumerate<std::str::Lines<'a>>,
+        ///how many lines the header took, so lines in the body can be numbered like in the file
+        offset: usize,
+        words: Vec<&'a str>,
+        next: usize,
+        line: usize,
+    },
+    Binary(Cursor<&'a [u8]>, Endian),
+}
+
+impl<'a> Body<'a> {
+    fn start_item(&mut self) -> Result<(), MeshError> {
+        if let Body::Text {
+            lines,
+            offset,
+            words,
+            next,
+            line,
+        } = self
+        {
+            //blank lines hold no items
+            let (l, text) = lines.find(|(_, t)| !t.trim().is_empty()).map_or_else(
+                || parse_error(*line + 1, "the file ends too early".into()),
+                Ok,
+            )?;
+            *line = *offset + l + 1;
+            *words = text.split_whitespace().collect();
+            *next = 0;
+        }
+        Ok(())
+    }
+
+    fn end_item(&self) -> Result<(), MeshError> {
+        match self {
+            Body::Text {
+                words, next, line, ..
+            } if *next < words.len() => parse_error(
+                *line,
+                format!(
+                    "{} values left over at the end of the line",
+                    words.len() - next
+                ),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    fn value(&mut self, ty: Type) -> Result<Float, MeshError> {
+        match self {
+            Body::Text {
+                words, next, line, ..
+            } => {
+                let word = match words.get(*next) {
+                    Some(w) => w,
+                    None => return parse_error(*line, "the line has too few values".into()),
+                };
+                *next += 1;
+                let value = match ty {
+                    Type::F32 | Type::F64 => word.parse::<Float>().ok(),
+                    _ => word.parse::<i64>().ok().map(|i| i as Float),
+                };
+                match value {
+                    Some(v) => Ok(v),
+                    None => parse_error(*line, format!("`{}` is not a {:?}", word, ty)),
+                }
+            }
+            Body::Binary(cursor, endian) => {
+                let e = *endian;
+                let pos = cursor.position();
+                let value: BinResult<Float> = (|| {
+                    Ok(match ty {
+                        Type::I8 => cursor.read_type::<i8>(e)? as Float,
+                        Type::U8 => cursor.read_type::<u8>(e)? as Float,
+                        Type::I16 => cursor.read_type::<i16>(e)? as Float,
+                        Type::U16 => cursor.read_type::<u16>(e)? as Float,
+                        Type::I32 => cursor.read_type::<i32>(e)? as Float,
+                        Type::U32 => cursor.read_type::<u32>(e)? as Float,
+                        Type::F32 => cursor.read_type::<f32>(e)? as Float,
+                        Type::F64 => cursor.read_type::<f64>(e)?,
+                    })
+                })();
+                match value {
+                    Err(e) if e.is_eof() => Err(binrw::Error::AssertFail {
+                        pos,
+                        message: "the file ends too early".into(),
+                    }
+                    .into()),
+                    v => Ok(v?),
+                }
+            }
+        }
+    }
+
+    ///the values of one item, each property gives one value or, if it is a list, its items
+    fn item(&mut self, element: &Element) -> Result<Vec<Vec<Float>>, MeshError> {
+        self.start_item()?;
+        let mut values = Vec::with_capacity(element.properties.len());
+        for p in &element.properties {
+            let len = match p.list {
+                Some(ty) => self.value(ty)? as usize,
+                None => 1,
+            };
+            values.push(
+                (0..len)
+                    .map(|_| self.value(p.ty))
+                    .collect::<Result<_, _>>()?,
+            );
+        }
+        self.end_item()?;
+        Ok(values)
+    }
+}
+
+///reads the header, giving the elements and how many bytes and lines the header took
+fn header(bytes: &[u8]) -> Result<(Format, Vec<Element>, usize, usize), MeshError> {
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut start = 0;
+    let mut line = 0;
+    loop {
+        let end = match bytes[start..].iter().position(|&b| b == b'\n') {
+            Some(i) => start + i,
+            None => return parse_error(line + 1, "the header has no `end_header`".into()),
+        };
+        line += 1;
+        let text = String::from_utf8_lossy(&bytes[start..end]);
+        start = end + 1;
+        let words: Vec<_> = text.split_whitespace().collect();
+        match words[..] {
+            ["ply"] if line == 1 => (),
+            _ if line == 1 => return parse_error(1, "not a PLY file".into()),
+            ["format", f, _] => {
+                format = Some(match f {
+                    "ascii" => Format::Text,
+                    "binary_little_endian" => Format::Binary(Endian::Little),
+                    "binary_big_endian" => Format::Binary(Endian::Big),
+                    _ => return parse_error(line, format!("`{}` is not a PLY format", f)),
+                })
+            }
+            ["element", name, count] => match count.parse() {
+                Ok(count) => elements.push(Element {
+                    name: name.into(),
+                    count,
+                    properties: Vec::new(),
+                }),
+                Err(_) => return parse_error(line, format!("`{}` is not a count", count)),
+            },
+            ["property", ..] => {
+                let element = match elements.last_mut() {
+                    Some(e) => e,
+                    None => return parse_error(line, "a property before any element".into()),
+                };
+                let property = match words[1..] {
+                    ["list", len, ty, name] => {
+                        Type::parse(len)
+                            .zip(Type::parse(ty))
+                            .map(|(len, ty)| Property {
+                                name: name.into(),
+                                ty,
+                                list: Some(len),
+                            })
+                    }
+                    [ty, name] => Type::parse(ty).map(|ty| Property {
+                        name: name.into(),
+                        ty,
+                        list: None,
+                    }),
+                    _ => None,
+                };
+                let property = match property {
+                    Some(p) => p,
+                    None => {
+                        return parse_error(line, format!("`{}` is not a property", text.trim()))
+                    }
+                };
+                element.properties.push(property);
+            }
+            ["end_header"] => break,
+            ["comment", ..] | ["obj_info", ..] | [] => (),
+            _ => {
+                return parse_error(
+                    line,
+                    format!("`{}` is not part of a PLY header", text.trim()),
+                )
+            }
+        }
+    }
+    match format {
+        Some(f) => Ok((f, elements, start, line)),
+        None => parse_error(
+            line,
+            "the header doesn't say what format the file is".into(),
+        ),
+    }
+}
+
+pub(super) fn parse(bytes: &[u8]) -> Result<Mesh, MeshError> {
+    let (format, elements, len, lines) = header(bytes)?;
+    let mut body = match format {
+        Format::Text => match std::str::from_utf8(&bytes[len..]) {
+            Ok(src) => Body::Text {
+                lines: src.lines().enumerate(),
+                offset: lines,
+                words: Vec::new(),
+                next: 0,
+                line: lines,
+            },
+            Err(_) => return parse_error(lines + 1, "an ascii PLY file is not text".into()),
+        },
+        Format::Binary(endian) => {
+            let mut cursor = Cursor::new(bytes);
+            cursor.set_position(len as u64);
+            Body::Binary(cursor, endian)
+        }
+    };
+
+    let mut mesh = Mesh::default();
+    for element in &elements {
+        let find = |name: &str| element.properties.iter().position(|p| p.name == name);
+        let all = |names: &[&str]| names.iter().map(|n| find(n)).collect::<Option<Vec<_>>>();
+        match element.name.as_str() {
+            "vertex" => {
+                let xyz = match all(&["x", "y", "z"]) {
+                    Some(xyz) => xyz,
+                    None => return parse_error(lines, "the vertices have no x, y, and z".into()),
+                };
+                let nxyz = all(&["nx", "ny", "nz"]);
+                let uv = all(&["u", "v"])
+                    .or_else(|| all(&["s", "t"]))
+                    .or_else(|| all(&["texture_u", "texture_v"]));
+                for _ in 0..element.count {
+                    let item = body.item(element)?;
+                    let at = |i: usize| item[i].first().copied().unwrap_or_default();
+                    mesh.positions.push((at(xyz[0]), at(xyz[1]), at(xyz[2])));
+                    if let Some(n) = &nxyz {
+                        mesh.normals.push((at(n[0]), at(n[1]), at(n[2])));
+                    }
+                    if let Some(uv) = &uv {
+                        mesh.uvs.push((at(uv[0]), at(uv[1])));
+                    }
+                }
+            }
+            "face" => {
+                let indices = find("vertex_indices").or_else(|| find("vertex_index"));
+                for _ in 0..element.count {
+                    let item = body.item(element)?;
+                    let indices = match indices {
+                        Some(i) => &item[i],
+                        None => continue,
+                    };
+                    let corners = indices
+                        .iter()
+                        .map(|&i| corner(&mesh, i))
+                        .collect::<Option<Vec<_>>>();
+                    let corners = match corners {
+                        Some(c) if c.len() >= 3 => c,
+                        Some(c) => {
+                            return face_error(
+                                &body,
+                                format!("a face needs at least 3 corners, found {}", c.len()),
+                            )
+                        }
+                        None => {
+                            return face_error(
+                                &body,
+                                format!(
+                                    "a face uses a vertex which doesn't exist, there are {}",
+                                    mesh.positions.len()
+                                ),
+                            )
+                        }
+                    };
+                    //faces with more corners are assumed to be flat and convex, like in OBJ
+                    for i in 1..corners.len() - 1 {
+                        mesh.faces.push([corners[0], corners[i], corners[i + 1]]);
+                    }
+                }
+            }
+            _ => {
+                for _ in 0..element.count {
+                    body.item(element)?;
+                }
+            }
+        }
+    }
+    Ok(mesh)
+}
+
+///every attribute of a vertex is stored with it, so one index picks all of them
+fn corner(mesh: &Mesh, i: Float) -> Option<Corner> {
+    if i < 0.0 {
+        return None;
+    }
+    let i = i as usize;
+    let has = |len: usize| (len > i).then_some(i);
+    Some(Corner {
+        position: has(mesh.positions.len())?,
+        uv: has(mesh.uvs.len()),
+        normal: has(mesh.normals.len()),
+    })
+}
+
+fn face_error(body: &Body, message: String) -> Result<Mesh, MeshError> {
+    match body {
+        Body::Text { line, .. } => parse_error(*line, message),
+        Body::Binary(cursor, _) => Err(binrw::Error::AssertFail {
+            pos: cursor.position(),
+            message,
+        }
+        .into()),
+    }
+}
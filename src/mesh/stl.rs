@@ -0,0 +1,205 @@
+//!STL, both the binary and the text kind, which are told apart by their size since binary files
+//!often start with `solid` too
+
+use std::collections::HashMap;
+
+use binrw::io::Cursor;
+use binrw::{BinRead, BinWrite};
+
+use super::{parse_error as error, Corner, Mesh, MeshError};
+use crate::gmath;
+use crate::space::Point;
+
+///bytes before the facets of a binary file, an 80 byte header nothing reads then the facet count
+const HEADER_LEN: usize = 84;
+///bytes in each facet of a binary file
+const FACET_LEN: usize = 50;
+
+#[derive(BinRead, BinWrite)]
+#[brw(little)]
+struct Binary {
+    header: [u8; 80],
+    //checked against the size of the file before it is read
+    count: u32,
+    #[br(count = count)]
+    facets: Vec<Facet>,
+}
+
+#[derive(BinRead, BinWrite)]
+#[brw(little)]
+struct Facet {
+    normal: [f32; 3],
+    corners: [[f32; 3]; 3],
+    attribute: u16,
+}
+
+///builds the mesh one facet at a time, STL gives every facet its own corners so ones at the same
+///spot are welded back together here
+#[derive(Default)]
+struct Welder {
+    mesh: Mesh,
+    seen: HashMap<[u64; 3], usize>,
+}
+
+impl Welder {
+    fn position(&mut self, p: Point) -> usize {
+        let positions = &mut self.mesh.positions;
+        *self
+            .seen
+            .entry([p.0.to_bits(), p.1.to_bits(), p.2.to_bits()])
+            .or_insert_with(|| {
+                positions.push(p);
+                positions.len() - 1
+            })
+    }
+
+    ///the corners should be counter clockwise seen from the side the normal points to, a facet
+    ///wound the other way is turned around so the normal wins, a zero normal leaves it alone
+    fn facet(&mut self, normal: Point, [p, q, r]: [Point; 3]) {
+        let (p, r) = if gmath::dot(normal, gmath::norm(p, q, r)) < 0.0 {
+            (r, p)
+        } else {
+            (p, r)
+        };
+        let face = [p, q, r].map(|c| Corner {
+            position: self.position(c),
+            uv: None,
+            normal: None,
+        });
+        self.mesh.faces.push(face);
+    }
+}
+
+fn point(p: [f32; 3]) -> Point {
+    (p[0] as f64, p[1] as f64, p[2] as f64)
+}
+
+pub(super) fn parse(bytes: &[u8]) -> Result<Mesh, MeshError> {
+    let binary = bytes.len() >= HEADER_LEN && {
+        let count = u32::from_le_bytes(bytes[80..84].try_into().unwrap()) as usize;
+        bytes.len() == HEADER_LEN + count * FACET_LEN
+    };
+    if !binary && bytes.starts_with(b"solid") {
+        if let Ok(src) = std::str::from_utf8(bytes) {
+            return parse_text(src);
+        }
+    }
+    parse_binary(bytes)
+}
+
+fn parse_binary(bytes: &[u8]) -> Result<Mesh, MeshError> {
+    //the count is checked against the size first so a bad one can't ask for a huge allocation
+    if bytes.len() < HEADER_LEN {
+        return Err(binrw::Error::AssertFail {
+            pos: bytes.len() as u64,
+            message: "the file is too short to be an STL file".into(),
+        }
+        .into());
+    }
+    let count = u32::from_le_bytes(bytes[80..84].try_into().unwrap()) as usize;
+    if bytes.len() != HEADER_LEN + count * FACET_LEN {
+        return Err(binrw::Error::AssertFail {
+            pos: 80,
+            message: format!(
+                "the file says it has {} facets which take {} bytes, but it is {} bytes",
+                count,
+                HEADER_LEN + count * FACET_LEN,
+                bytes.len()
+            ),
+        }
+        .into());
+    }
+    let stl = Binary::read(&mut Cursor::new(bytes))?;
+    let mut welder = Welder::default();
+    for f in stl.facets {
+        welder.facet(point(f.normal), f.corners.map(point));
+    }
+    Ok(welder.mesh)
+}
+
+fn parse_text(src: &str) -> Result<Mesh, MeshError> {
+    let mut welder = Welder::default();
+    let mut normal = (0.0, 0.0, 0.0);
+    let mut corners = Vec::with_capacity(3);
+    for (l, text) in src.lines().enumerate() {
+        let line = l + 1;
+        let mut words = text.split_whitespace();
+        let numbers = |words: std::str::SplitWhitespace| -> Result<Point, MeshError> {
+            let n = words
+                .map(|w| w.parse::<f64>())
+                .collect::<Result<Vec<_>, _>>();
+            match n.as_deref() {
+                Ok(&[x, y, z]) => Ok((x, y, z)),
+                _ => error(
+                    line,
+                    format!("`{}` should be followed by 3 numbers", text.trim()),
+                ),
+            }
+        };
+        match words.next() {
+            Some("facet") => {
+                if words.next() != Some("normal") {
+                    return error(line, "expected `normal` after `facet`".into());
+                }
+                normal = numbers(words)?;
+                corners.clear();
+            }
+            Some("vertex") => {
+                if corners.len() == 3 {
+                    return error(line, "a facet can only have 3 vertices".into());
+                }
+                corners.push(numbers(words)?);
+            }
+            Some("endfacet") => {
+                if corners.len() != 3 {
+                    return error(
+                        line,
+                        format!("a facet needs 3 vertices, found {}", corners.len()),
+                    );
+                }
+                welder.facet(normal, [corners[0], corners[1], corners[2]]);
+                corners.clear();
+            }
+            Some("solid" | "endsolid" | "outer" | "endloop") | None => (),
+            Some(w) => return error(line, format!("`{}` is not part of an STL file", w)),
+        }
+    }
+    Ok(welder.mesh)
+}
+
+///a binary STL file of the mesh, normals are taken from the corners since STL has no other kind
+pub(super) fn write(mesh: &Mesh) -> Vec<u8> {
+    let mut header = [0u8; 80];
+    let name = b"exported from MDL";
+    header[..name.len()].copy_from_slice(name);
+    let facets: Vec<_> = mesh
+        .faces
+        .iter()
+        .map(|f| {
+            let [p, q, r] = f.map(|c| mesh.positions[c.position]);
+            let f32s = |p: Point| [p.0 as f32, p.1 as f32, p.2 as f32];
+            let n = gmath::norm(p, q, r);
+            //a triangle with no area has no direction, STL readers take a zero normal to mean that
+            let n = if n == (0.0, 0.0, 0.0) {
+                n
+            } else {
+                gmath::normalize(n)
+            };
+            Facet {
+                normal: f32s(n),
+                corners: [f32s(p), f32s(q), f32s(r)],
+                attribute: 0,
+            }
+        })
+        .collect();
+    let stl = Binary {
+        header,
+        count: u32::try_from(facets.len()).expect("too many triangles for an STL file"),
+        facets,
+    };
+    let mut out = Cursor::new(Vec::new());
+    //writing to memory can't fail
+    stl.write_to(&mut out)
+        .expect("failed to write STL to memory");
+    out.into_inner()
+}
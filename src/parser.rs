@@ -3,20 +3,59 @@ use std::fs::{self, File};
 use std::io::Write;
 use std::path::PathBuf;
 use std::process;
-use std::process::Stdio;
 
-use crate::{Color, Engine};
-use binrw::{BinRead, NullString};
+use crate::diagnostic::{Code, Diagnostics, Span};
+use crate::{Color, Easing, Engine, KnobError, Light, Resolution, Shading, Spline, MIN_RESOLUTION};
+use binrw::io::Cursor;
+use binrw::{BinRead, BinResult, BinWrite, NullString};
 use rayon::prelude::*;
 
-#[derive(BinRead, PartialEq, Debug)]
+mod disasm;
+mod format;
+mod lexer;
+mod mdl;
+
+pub use format::{FORMAT_MAGIC, FORMAT_VERSION};
+
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
 enum Knob {
-    #[br(magic = 0x0u8)]
+    #[brw(magic = 0x0u8)]
     Constant,
-    #[br(magic = 0x1u8)]
+    #[brw(magic = 0x1u8)]
+    Name(NullString),
+}
+
+///which coordinate system a shape is drawn in
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
+enum CoordSystem {
+    #[brw(magic = 0x0u8)]
+    Stack,
+    #[brw(magic = 0x1u8)]
     Name(NullString),
 }
 
+impl CoordSystem {
+    fn name(&self) -> Option<String> {
+        match self {
+            CoordSystem::Stack => None,
+            CoordSystem::Name(name) => Some(name.to_string()),
+        }
+    }
+
+    ///makes the coordinate system the top of the stack, until `leave`
+    fn enter<T: Color>(&self, engine: &mut Engine<T>) {
+        if let CoordSystem::Name(name) = self {
+            engine.push_coord_system(&name.to_string());
+        }
+    }
+
+    fn leave<T: Color>(&self, engine: &mut Engine<T>) {
+        if let CoordSystem::Name(_) = self {
+            engine.pop_sys();
+        }
+    }
+}
+
 trait Run {
     fn run<T: Color>(&self, engine: &mut Engine<T>);
 }
@@ -26,7 +65,7 @@ trait Knobular {
     fn go<T: Color>(&self, engine: &mut Engine<T>, frame: u32);
 }
 
-#[derive(BinRead, PartialEq, Debug)]
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
 struct PushCommand {}
 
 impl Run for PushCommand {
@@ -35,7 +74,7 @@ impl Run for PushCommand {
     }
 }
 
-#[derive(BinRead, PartialEq, Debug)]
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
 struct PopCommand {}
 
 impl Run for PopCommand {
@@ -44,7 +83,7 @@ impl Run for PopCommand {
     }
 }
 
-#[derive(BinRead, PartialEq, Debug)]
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
 struct MoveCommand {
     x: f64,
     y: f64,
@@ -52,12 +91,6 @@ struct MoveCommand {
     knob: Knob,
 }
 
-impl Run for MoveCommand {
-    fn run<T: Color>(&self, engine: &mut Engine<T>) {
-        engine.move_sys(self.x, self.y, self.z);
-    }
-}
-
 impl Knobular for MoveCommand {
     fn go<T: Color>(&self, engine: &mut Engine<T>, frame: u32) {
         let v = match self.knob {
@@ -69,19 +102,13 @@ impl Knobular for MoveCommand {
     }
 }
 
-#[derive(BinRead, PartialEq, Debug)]
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
 struct RotateCommand {
     axis: f64,
     theta: f64,
     knob: Knob,
 }
 
-impl Run for RotateCommand {
-    fn run<T: Color>(&self, engine: &mut Engine<T>) {
-        engine.rotate_sys(self.axis, self.theta);
-    }
-}
-
 impl Knobular for RotateCommand {
     fn go<T: Color>(&self, engine: &mut Engine<T>, frame: u32) {
         let v = match self.knob {
@@ -93,7 +120,7 @@ impl Knobular for RotateCommand {
     }
 }
 
-#[derive(BinRead, PartialEq, Debug)]
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
 struct ScaleCommand {
     x: f64,
     y: f64,
@@ -101,12 +128,6 @@ struct ScaleCommand {
     knob: Knob,
 }
 
-impl Run for ScaleCommand {
-    fn run<T: Color>(&self, engine: &mut Engine<T>) {
-        engine.scale_sys(self.x, self.y, self.z);
-    }
-}
-
 impl Knobular for ScaleCommand {
     fn go<T: Color>(&self, engine: &mut Engine<T>, frame: u32) {
         let v = match self.knob {
@@ -118,7 +139,37 @@ impl Knobular for ScaleCommand {
     }
 }
 
-#[derive(BinRead, PartialEq, Debug)]
+///how finely round shapes are drawn
+#[derive(BinRead, BinWrite, PartialEq, Clone, Copy, Debug)]
+enum ResolutionMode {
+    #[brw(magic = 0x0u8)]
+    Steps(u32),
+    #[brw(magic = 0x1u8)]
+    Auto,
+    #[brw(magic = 0x2u8)]
+    Tolerance(f64),
+}
+
+impl From<ResolutionMode> for Resolution {
+    fn from(mode: ResolutionMode) -> Self {
+        match mode {
+            ResolutionMode::Steps(n) => Resolution::Steps(n as usize),
+            ResolutionMode::Auto => Resolution::Auto,
+            ResolutionMode::Tolerance(t) => Resolution::Tolerance(t),
+        }
+    }
+}
+
+///the resolution a shape asked for, if it didn't it uses the engine's
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
+enum ShapeResolution {
+    #[brw(magic = 0x0u8)]
+    Engine,
+    #[brw(magic = 0x1u8)]
+    Own(ResolutionMode),
+}
+
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
 struct BoxCommand {
     x: f64,
     y: f64,
@@ -127,40 +178,51 @@ struct BoxCommand {
     w: f64,
     d: f64,
     constants: [f64; 9],
+    coord_system: CoordSystem,
 }
 
 impl Run for BoxCommand {
     fn run<T: Color>(&self, engine: &mut Engine<T>) {
         engine.set_constants(self.constants);
+        self.coord_system.enter(engine);
         engine.add_box((self.x, self.y, self.z), self.h, self.w, self.d);
         engine.apply_sys();
+        self.coord_system.leave(engine);
         engine.draw_space();
         engine.clear_lines();
         engine.clear_tris();
     }
 }
 
-#[derive(BinRead, PartialEq, Debug)]
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
 struct SphereCommand {
     x: f64,
     y: f64,
     z: f64,
     r: f64,
     constants: [f64; 9],
+    resolution: ShapeResolution,
+    coord_system: CoordSystem,
 }
 
 impl Run for SphereCommand {
     fn run<T: Color>(&self, engine: &mut Engine<T>) {
         engine.set_constants(self.constants);
-        engine.add_sphere((self.x, self.y, self.z), self.r);
+        self.coord_system.enter(engine);
+        let c = (self.x, self.y, self.z);
+        match self.resolution {
+            ShapeResolution::Engine => engine.add_sphere(c, self.r),
+            ShapeResolution::Own(res) => engine.add_sphere_with(c, self.r, res.into()),
+        }
         engine.apply_sys();
+        self.coord_system.leave(engine);
         engine.draw_space();
         engine.clear_lines();
         engine.clear_tris();
     }
 }
 
-#[derive(BinRead, PartialEq, Debug)]
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
 struct TorusCommand {
     x: f64,
     y: f64,
@@ -168,20 +230,28 @@ struct TorusCommand {
     r0: f64,
     r1: f64,
     constants: [f64; 9],
+    resolution: ShapeResolution,
+    coord_system: CoordSystem,
 }
 
 impl Run for TorusCommand {
     fn run<T: Color>(&self, engine: &mut Engine<T>) {
         engine.set_constants(self.constants);
-        engine.add_torus((self.x, self.y, self.z), self.r0, self.r1);
+        self.coord_system.enter(engine);
+        let c = (self.x, self.y, self.z);
+        match self.resolution {
+            ShapeResolution::Engine => engine.add_torus(c, self.r0, self.r1),
+            ShapeResolution::Own(res) => engine.add_torus_with(c, self.r0, self.r1, res.into()),
+        }
         engine.apply_sys();
+        self.coord_system.leave(engine);
         engine.draw_space();
         engine.clear_lines();
         engine.clear_tris();
     }
 }
 
-#[derive(BinRead, PartialEq, Debug)]
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
 struct LineCommand {
     x0: f64,
     y0: f64,
@@ -189,19 +259,60 @@ struct LineCommand {
     x1: f64,
     y1: f64,
     z1: f64,
+    coord_system0: CoordSystem,
+    coord_system1: CoordSystem,
 }
 
 impl Run for LineCommand {
     fn run<T: Color>(&self, engine: &mut Engine<T>) {
-        engine.add_line((self.x0, self.y0, self.z0), (self.x1, self.y1, self.z1));
+        //the ends are transformed as they are added, each in its own coordinate system
+        engine.add_line_in(
+            (self.x0, self.y0, self.z0),
+            self.coord_system0.name().as_deref(),
+            (self.x1, self.y1, self.z1),
+            self.coord_system1.name().as_deref(),
+        );
+        engine.draw_space();
+        engine.clear_lines();
+        engine.clear_tris();
+    }
+}
+
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
+struct MeshCommand {
+    file: NullString,
+    constants: [f64; 9],
+    coord_system: CoordSystem,
+}
+
+impl Run for MeshCommand {
+    fn run<T: Color>(&self, engine: &mut Engine<T>) {
+        engine.set_constants(self.constants);
+        self.coord_system.enter(engine);
+        //every mesh is loaded before the script is run, so this can't fail
+        engine
+            .add_mesh_file(&self.file.to_string())
+            .expect("mesh was not loaded before running the script");
         engine.apply_sys();
+        self.coord_system.leave(engine);
         engine.draw_space();
         engine.clear_lines();
         engine.clear_tris();
     }
 }
 
-#[derive(BinRead, PartialEq, Debug)]
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
+struct SaveCoordSystemCommand {
+    name: NullString,
+}
+
+impl Run for SaveCoordSystemCommand {
+    fn run<T: Color>(&self, engine: &mut Engine<T>) {
+        engine.save_coord_system(&self.name.to_string());
+    }
+}
+
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
 struct SaveCommand {
     file: NullString,
 }
@@ -222,7 +333,7 @@ impl Run for SaveCommand {
     }
 }
 
-#[derive(BinRead, PartialEq, Debug)]
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
 struct DisplayCommand {}
 
 impl Run for DisplayCommand {
@@ -244,201 +355,908 @@ impl Run for DisplayCommand {
     }
 }
 
-#[derive(BinRead, PartialEq, Debug)]
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
 struct BasenameCommand {
     basename: NullString,
 }
 
-#[derive(BinRead, PartialEq, Debug)]
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
 struct FramesCommand {
     frames: u32,
 }
 
-#[derive(BinRead, PartialEq, Debug)]
+///how a knob is spaced out over the frames it is varied in
+#[derive(BinRead, BinWrite, PartialEq, Clone, Copy, Debug)]
+enum EasingMode {
+    #[brw(magic = 0x0u8)]
+    Linear,
+    #[brw(magic = 0x1u8)]
+    EaseIn,
+    #[brw(magic = 0x2u8)]
+    EaseOut,
+    #[brw(magic = 0x3u8)]
+    EaseInOut,
+    #[brw(magic = 0x4u8)]
+    Cubic,
+    #[brw(magic = 0x5u8)]
+    Sine,
+    #[brw(magic = 0x6u8)]
+    Bounce,
+    #[brw(magic = 0x7u8)]
+    Step(u32),
+    #[brw(magic = 0x8u8)]
+    CubicBezier([f64; 4]),
+}
+
+impl From<EasingMode> for Easing {
+    fn from(mode: EasingMode) -> Self {
+        match mode {
+            EasingMode::Linear => Easing::Linear,
+            EasingMode::EaseIn => Easing::EaseIn,
+            EasingMode::EaseOut => Easing::EaseOut,
+            EasingMode::EaseInOut => Easing::EaseInOut,
+            EasingMode::Cubic => Easing::Cubic,
+            EasingMode::Sine => Easing::Sine,
+            EasingMode::Bounce => Easing::Bounce,
+            EasingMode::Step(n) => Easing::Step(n),
+            EasingMode::CubicBezier([x1, y1, x2, y2]) => Easing::CubicBezier(x1, y1, x2, y2),
+        }
+    }
+}
+
+impl EasingMode {
+    ///what is wrong with the easing, if it can't be used
+    fn problem(&self) -> Option<String> {
+        match *self {
+            EasingMode::Step(0) => Some("`step` needs at least 1 step".into()),
+            EasingMode::CubicBezier([x1, _, x2, _])
+                if !(0.0..=1.0).contains(&x1) || !(0.0..=1.0).contains(&x2) =>
+            {
+                Some(format!(
+                    "the x of each `cubic-bezier` control point must be from 0 to 1, not {} and {}",
+                    x1, x2
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
 struct VaryCommand {
     knob: NullString,
     start_frame: u32,
     end_frame: u32,
     start_val: f64,
     end_val: f64,
+    easing: EasingMode,
+}
+
+///the keys of a spline, which numbers each key has depends on the kind of spline
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
+enum SplineKeys {
+    #[brw(magic = 0x0u8)]
+    CatmullRom {
+        count: u16,
+        #[br(count = count)]
+        keys: Vec<[f64; 2]>,
+    },
+    #[brw(magic = 0x1u8)]
+    Hermite {
+        count: u16,
+        #[br(count = count)]
+        keys: Vec<[f64; 3]>,
+    },
+    #[brw(magic = 0x2u8)]
+    Bezier {
+        count: u16,
+        #[br(count = count)]
+        points: Vec<[f64; 2]>,
+    },
+}
+
+impl SplineKeys {
+    fn spline(&self) -> Spline {
+        match self {
+            SplineKeys::CatmullRom { keys, .. } => {
+                Spline::CatmullRom(keys.iter().map(|&[f, v]| (f, v)).collect())
+            }
+            SplineKeys::Hermite { keys, .. } => {
+                Spline::Hermite(keys.iter().map(|&[f, v, s]| (f, v, s)).collect())
+            }
+            SplineKeys::Bezier { points, .. } => {
+                Spline::Bezier(points.iter().map(|&[f, v]| (f, v)).collect())
+            }
+        }
+    }
+}
+
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
+struct SplineCommand {
+    knob: NullString,
+    keys: SplineKeys,
+}
+
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
+struct SetCommand {
+    knob: NullString,
+    value: f64,
+}
+
+impl Run for SetCommand {
+    fn run<T: Color>(&self, engine: &mut Engine<T>) {
+        engine.set_knob(&self.knob.to_string(), self.value);
+    }
+}
+
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
+struct SetKnobsCommand {
+    value: f64,
+}
+
+impl Run for SetKnobsCommand {
+    fn run<T: Color>(&self, engine: &mut Engine<T>) {
+        engine.set_knobs(self.value);
+    }
+}
+
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
+struct SaveKnobsCommand {
+    list: NullString,
+}
+
+impl Run for SaveKnobsCommand {
+    fn run<T: Color>(&self, engine: &mut Engine<T>) {
+        engine.save_knobs(&self.list.to_string());
+    }
+}
+
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
+struct TweenCommand {
+    start_frame: u32,
+    end_frame: u32,
+    list0: NullString,
+    list1: NullString,
+}
+
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
+struct LightCommand {
+    r: f64,
+    g: f64,
+    b: f64,
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+impl Run for LightCommand {
+    fn run<T: Color>(&self, engine: &mut Engine<T>) {
+        let col = T::from_rgb((self.r, self.g, self.b));
+        engine.add_light(Light::new((self.x, self.y, self.z), col));
+    }
+}
+
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
+struct AmbientCommand {
+    r: f64,
+    g: f64,
+    b: f64,
+    knob: Knob,
+}
+
+impl Knobular for AmbientCommand {
+    fn go<T: Color>(&self, engine: &mut Engine<T>, frame: u32) {
+        let v = match self.knob {
+            Knob::Constant => 1.0,
+            Knob::Name(ref name) => engine.get_knob_val(name.to_string(), frame),
+        };
+
+        engine.set_ambient_light(T::from_rgb((self.r * v, self.g * v, self.b * v)));
+    }
+}
+
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
+struct CameraCommand {
+    eye_x: f64,
+    eye_y: f64,
+    eye_z: f64,
+    aim_x: f64,
+    aim_y: f64,
+    aim_z: f64,
+}
+
+impl Run for CameraCommand {
+    fn run<T: Color>(&self, engine: &mut Engine<T>) {
+        engine.look_at(
+            (self.eye_x, self.eye_y, self.eye_z),
+            (self.aim_x, self.aim_y, self.aim_z),
+        );
+    }
+}
+
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
+struct FocalCommand {
+    focal: f64,
+}
+
+impl Run for FocalCommand {
+    fn run<T: Color>(&self, engine: &mut Engine<T>) {
+        engine.set_focal(self.focal);
+    }
 }
 
-#[derive(BinRead, PartialEq, Debug)]
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
+enum ShadingMode {
+    #[brw(magic = 0x0u8)]
+    Wireframe,
+    #[brw(magic = 0x1u8)]
+    Flat,
+    #[brw(magic = 0x2u8)]
+    Gouraud,
+    #[brw(magic = 0x3u8)]
+    Phong,
+}
+
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
+struct ShadingCommand {
+    mode: ShadingMode,
+}
+
+impl Run for ShadingCommand {
+    fn run<T: Color>(&self, engine: &mut Engine<T>) {
+        engine.set_shading(match self.mode {
+            ShadingMode::Wireframe => Shading::Wireframe,
+            ShadingMode::Flat => Shading::Flat,
+            ShadingMode::Gouraud => Shading::Gouraud,
+            ShadingMode::Phong => Shading::Phong,
+        });
+    }
+}
+
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
+struct ResolutionCommand {
+    mode: ResolutionMode,
+}
+
+impl Run for ResolutionCommand {
+    fn run<T: Color>(&self, engine: &mut Engine<T>) {
+        engine.set_resolution(self.mode.into());
+    }
+}
+
+#[derive(BinRead, BinWrite, PartialEq, Debug)]
 enum Command {
-    #[br(magic = 0x1u8)]
+    #[brw(magic = 0x1u8)]
     Push(PushCommand),
-    #[br(magic = 0x2u8)]
+    #[brw(magic = 0x2u8)]
     Pop(PopCommand),
-    #[br(magic = 0x3u8)]
+    #[brw(magic = 0x3u8)]
     Move(MoveCommand),
-    #[br(magic = 0x4u8)]
+    #[brw(magic = 0x4u8)]
     Rotate(RotateCommand),
-    #[br(magic = 0x5u8)]
+    #[brw(magic = 0x5u8)]
     Scale(ScaleCommand),
-    #[br(magic = 0x6u8)]
+    #[brw(magic = 0x6u8)]
     Box(BoxCommand),
-    #[br(magic = 0x7u8)]
+    #[brw(magic = 0x7u8)]
     Sphere(SphereCommand),
-    #[br(magic = 0x8u8)]
+    #[brw(magic = 0x8u8)]
     Torus(TorusCommand),
-    #[br(magic = 0x9u8)]
+    #[brw(magic = 0x9u8)]
     Line(LineCommand),
-    #[br(magic = 0xAu8)]
+    #[brw(magic = 0xAu8)]
     Save(SaveCommand),
-    #[br(magic = 0xBu8)]
+    #[brw(magic = 0xBu8)]
     Display(DisplayCommand),
-    #[br(magic = 0xCu8)]
+    #[brw(magic = 0xCu8)]
     Basename(BasenameCommand),
-    #[br(magic = 0xDu8)]
+    #[brw(magic = 0xDu8)]
     Frames(FramesCommand),
-    #[br(magic = 0xEu8)]
+    #[brw(magic = 0xEu8)]
     Vary(VaryCommand),
-    #[br(magic = 0x0u8)]
+    #[brw(magic = 0xFu8)]
+    Light(LightCommand),
+    #[brw(magic = 0x10u8)]
+    Ambient(AmbientCommand),
+    #[brw(magic = 0x11u8)]
+    Camera(CameraCommand),
+    #[brw(magic = 0x12u8)]
+    Focal(FocalCommand),
+    #[brw(magic = 0x13u8)]
+    Shading(ShadingCommand),
+    #[brw(magic = 0x14u8)]
+    Resolution(ResolutionCommand),
+    #[brw(magic = 0x15u8)]
+    Mesh(MeshCommand),
+    #[brw(magic = 0x16u8)]
+    SaveCoordSystem(SaveCoordSystemCommand),
+    #[brw(magic = 0x17u8)]
+    Set(SetCommand),
+    #[brw(magic = 0x18u8)]
+    SetKnobs(SetKnobsCommand),
+    #[brw(magic = 0x19u8)]
+    SaveKnobs(SaveKnobsCommand),
+    #[brw(magic = 0x1Au8)]
+    Tween(TweenCommand),
+    #[brw(magic = 0x1Bu8)]
+    Spline(SplineCommand),
+    #[brw(magic = 0x0u8)]
     End,
 }
 
-#[derive(BinRead, Debug)]
+impl Command {
+    ///the knob a transformation is scaled by, if it has one
+    fn knob(&self) -> Option<&NullString> {
+        let knob = match self {
+            Command::Move(MoveCommand { knob, .. }) => knob,
+            Command::Rotate(RotateCommand { knob, .. }) => knob,
+            Command::Scale(ScaleCommand { knob, .. }) => knob,
+            Command::Ambient(AmbientCommand { knob, .. }) => knob,
+            _ => return None,
+        };
+        match knob {
+            Knob::Constant => None,
+            Knob::Name(name) => Some(name),
+        }
+    }
+
+    ///the saved coordinate systems a shape is drawn in, none if it is drawn in the top of the stack
+    fn coord_systems(&self) -> Vec<&NullString> {
+        let systems = match self {
+            Command::Box(BoxCommand { coord_system, .. })
+            | Command::Sphere(SphereCommand { coord_system, .. })
+            | Command::Torus(TorusCommand { coord_system, .. })
+            | Command::Mesh(MeshCommand { coord_system, .. }) => vec![coord_system],
+            Command::Line(LineCommand {
+                coord_system0,
+                coord_system1,
+                ..
+            }) => vec![coord_system0, coord_system1],
+            _ => Vec::new(),
+        };
+        systems
+            .into_iter()
+            .filter_map(|sys| match sys {
+                CoordSystem::Stack => None,
+                CoordSystem::Name(name) => Some(name),
+            })
+            .collect()
+    }
+
+    ///the resolution a command asks for, if it asks for one
+    fn resolution(&self) -> Option<ResolutionMode> {
+        match self {
+            Command::Resolution(ResolutionCommand { mode }) => Some(*mode),
+            Command::Sphere(SphereCommand { resolution, .. })
+            | Command::Torus(TorusCommand { resolution, .. }) => match resolution {
+                ShapeResolution::Engine => None,
+                ShapeResolution::Own(mode) => Some(*mode),
+            },
+            _ => None,
+        }
+    }
+}
+
+///a whole MDL script, read from and written to the intermediate format with the header and
+///checksum described in mdl_intermediate_language.spec
+#[derive(Debug)]
 pub struct Script {
-    #[br(parse_with = binrw::until(|com| *com == Command::End))]
     commands: Vec<Command>,
+    ///where each command came from in the source, empty if the script was not parsed from source
+    spans: Vec<Span>,
+}
+
+///scripts are equal if they have the same commands, no matter where they were read from
+impl PartialEq for Script {
+    fn eq(&self, other: &Self) -> bool {
+        self.commands == other.commands
+    }
 }
 
 impl Script {
-    pub fn exec<T: Color>(self, eng: &mut Engine<T>) {
-        // if there are more than one frames/basenames commands, we take the last one stated
-        let has_frames = self
+    ///reads a script from the bytes of an intermediate file
+    pub fn from_bytes(bytes: &[u8]) -> BinResult<Self> {
+        Self::read(&mut Cursor::new(bytes))
+    }
+
+    ///writes the script in the format described in mdl_intermediate_language.spec
+    ///reading the bytes back with `from_bytes` gives the same script
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Cursor::new(Vec::new());
+        //writing to memory can't fail
+        self.write_to(&mut out)
+            .expect("failed to write script to memory");
+        out.into_inner()
+    }
+
+    ///looks for problems which would stop the script from running properly and adds them to
+    ///`diag`, problems are given a location when the script was parsed from source
+    pub fn check(&self, diag: &mut Diagnostics) {
+        let span = |i: usize| self.spans.get(i).copied();
+        //the last one is the one used, as in `exec`
+        let frames = self.commands.iter().rev().find_map(|x| match x {
+            Command::Frames(FramesCommand { frames }) => Some(*frames),
+            _ => None,
+        });
+        let has_frames = frames.is_some();
+        let has_basename = self
             .commands
             .iter()
-            .rev()
-            .find(|x| matches!(x, Command::Frames(_)));
-        let has_basename = self
+            .any(|x| matches!(x, Command::Basename(_)));
+        let given: Vec<_> = self
             .commands
             .iter()
-            .rev()
-            .find(|x| matches!(x, Command::Basename(_)));
-        let has_vary = self.commands.iter().any(|x| matches!(x, Command::Vary(_)));
-
-        if has_vary && has_frames == None {
-            panic!(
-                "this is all a terrible misunderstanding, 
-            you need a frames command with your vary"
-            );
-        }
-
-        if has_frames != None && has_basename == None {
-            println!(
-                "woah there, you either forgot to put a basename or want to use the default,
-            be sure that is actually what you want buddy"
-            );
-        }
+            .filter_map(|x| match x {
+                Command::Vary(VaryCommand { knob, .. })
+                | Command::Set(SetCommand { knob, .. })
+                | Command::Spline(SplineCommand { knob, .. }) => Some(knob),
+                _ => None,
+            })
+            .collect();
+        //every knob is in every saved list, so these give all of them a value
+        let all_given = self
+            .commands
+            .iter()
+            .any(|x| matches!(x, Command::SetKnobs(_) | Command::Tween(_)));
 
-        let basename = match has_basename {
-            None => "generic_mdl_animation".to_string(),
-            Some(s) => {
-                if let Command::Basename(BasenameCommand { basename }) = s {
-                    basename.to_string()
-                } else {
-                    "generic_mdl_animation".to_string()
+        let mut depth = 0usize;
+        //coordinate systems are saved as the script runs, so one can only be used after it
+        let mut saved = Vec::new();
+        //and so are knob lists
+        let mut lists = Vec::new();
+        //the ranges of the `vary` commands so far, by knob
+        let mut ranges: Vec<(&NullString, u32, u32)> = Vec::new();
+        for (i, com) in self.commands.iter().enumerate() {
+            match com {
+                Command::Push(_) => depth += 1,
+                Command::Pop(_) if depth == 0 => diag.report(
+                    Code::UnbalancedPop,
+                    span(i),
+                    "`pop` without a matching `push`, there is nothing to pop".into(),
+                ),
+                Command::Pop(_) => depth -= 1,
+                Command::Rotate(RotateCommand { axis, .. })
+                    if *axis != 0.0 && *axis != 1.0 && *axis != 2.0 =>
+                {
+                    diag.report(
+                        Code::InvalidAxis,
+                        span(i),
+                        format!("{} is not an axis, expected 0, 1, or 2", axis),
+                    )
+                }
+                Command::Camera(c)
+                    if (c.eye_x, c.eye_y, c.eye_z) == (c.aim_x, c.aim_y, c.aim_z) =>
+                {
+                    diag.report(
+                        Code::CameraLooksAtItself,
+                        span(i),
+                        "the camera's eye and aim are the same point so it isn't looking anywhere"
+                            .into(),
+                    )
                 }
+                Command::Focal(FocalCommand { focal }) if *focal <= 0.0 => diag.report(
+                    Code::InvalidFocal,
+                    span(i),
+                    format!("the focal length must be more than 0, not {}", focal),
+                ),
+                Command::Vary(_) if !has_frames => diag.report(
+                    Code::VaryWithoutFrames,
+                    span(i),
+                    "`vary` needs a `frames` command to say how long the animation is".into(),
+                ),
+                Command::Spline(_) if !has_frames => diag.report(
+                    Code::VaryWithoutFrames,
+                    span(i),
+                    "`spline` needs a `frames` command to say how long the animation is".into(),
+                ),
+                Command::Tween(TweenCommand { list0, list1, .. }) => {
+                    if !has_frames {
+                        diag.report(
+                            Code::VaryWithoutFrames,
+                            span(i),
+                            "`tween` needs a `frames` command to say how long the animation is"
+                                .into(),
+                        );
+                    }
+                    for list in [list0, list1] {
+                        if !lists.contains(&list) {
+                            diag.report(
+                                Code::UnknownKnobList,
+                                span(i),
+                                format!(
+                                    "knob list `{}` is tweened before it is saved with `save_knobs`",
+                                    list.to_string()
+                                ),
+                            );
+                        }
+                    }
+                }
+                Command::SaveKnobs(SaveKnobsCommand { list }) => lists.push(list),
+                Command::Frames(_) if !has_basename => diag.report(
+                    Code::MissingBasename,
+                    span(i),
+                    "no `basename` given, the animation will be saved as generic_mdl_animation.gif"
+                        .into(),
+                ),
+                Command::Basename(_) if !has_frames => diag.report(
+                    Code::UnusedBasename,
+                    span(i),
+                    "`basename` does nothing without a `frames` command".into(),
+                ),
+                Command::SaveCoordSystem(SaveCoordSystemCommand { name }) => saved.push(name),
+                _ => (),
             }
-        };
-
-        if let Some(frames_command) = has_frames {
-            //create an animation
-            let frames;
-            if let &Command::Frames(FramesCommand { frames: x }) = frames_command {
-                frames = x;
-            } else {
-                panic!("bad bad very bad, I literly filtered for only frame commands");
+            for name in com.coord_systems() {
+                if !saved.contains(&name) {
+                    diag.report(
+                        Code::UnknownCoordSystem,
+                        span(i),
+                        format!(
+                            "coordinate system `{}` is used before it is saved with \
+                             `save_coord_system`",
+                            name.to_string()
+                        ),
+                    );
+                }
             }
-            for v in self
-                .commands
-                .iter()
-                .filter(|x| matches!(x, Command::Vary(_)))
-            {
-                if let Command::Vary(vc) = v {
-                    let VaryCommand {
+            if let Command::Vary(VaryCommand { easing, .. }) = com {
+                if let Some(problem) = easing.problem() {
+                    diag.report(Code::InvalidEasing, span(i), problem);
+                }
+            }
+            let range = match com {
+                Command::Vary(VaryCommand {
+                    start_frame,
+                    end_frame,
+                    ..
+                }) => Some(("vary", *start_frame, *end_frame)),
+                Command::Tween(TweenCommand {
+                    start_frame,
+                    end_frame,
+                    ..
+                }) => Some(("tween", *start_frame, *end_frame)),
+                _ => None,
+            };
+            match (range, frames) {
+                (Some((what, start, end)), _) if end < start => diag.report(
+                    Code::InvalidFrameRange,
+                    span(i),
+                    format!(
+                        "`{}` ends at frame {} before it starts at frame {}",
+                        what, end, start
+                    ),
+                ),
+                (Some((what, _, end)), Some(frames)) if end >= frames => diag.report(
+                    Code::InvalidFrameRange,
+                    span(i),
+                    format!(
+                        "`{}` ends at frame {} but the animation has {} frames, the last is {}",
+                        what,
+                        end,
+                        frames,
+                        frames as i64 - 1
+                    ),
+                ),
+                _ => {
+                    if let Command::Vary(VaryCommand {
                         knob,
                         start_frame,
                         end_frame,
-                        start_val,
-                        end_val,
-                    } = vc;
-                    eng.add_vary(
-                        knob.to_string(),
-                        *start_frame,
-                        *end_frame,
-                        *start_val,
-                        *end_val,
-                        frames,
+                        ..
+                    }) = com
+                    {
+                        //ranges may share the frame one ends and the next starts on
+                        let overlap = ranges.iter().find(|(k, start, end)| {
+                            *k == knob && *start_frame < *end && *start < *end_frame
+                        });
+                        if let Some((_, start, end)) = overlap {
+                            diag.report(
+                                Code::OverlappingVary,
+                                span(i),
+                                format!(
+                                    "`vary` of `{}` from frame {} to {} overlaps the one from {} \
+                                     to {}, this one is used in the frames they share",
+                                    knob.to_string(),
+                                    start_frame,
+                                    end_frame,
+                                    start,
+                                    end
+                                ),
+                            );
+                        }
+                        ranges.push((knob, *start_frame, *end_frame));
+                    }
+                }
+            }
+            if let Command::Spline(SplineCommand { keys, .. }) = com {
+                if let Some(problem) = keys.spline().problem() {
+                    diag.report(Code::InvalidSpline, span(i), problem);
+                }
+            }
+            match com.resolution() {
+                Some(ResolutionMode::Steps(n)) if (n as usize) < MIN_RESOLUTION => diag.report(
+                    Code::InvalidResolution,
+                    span(i),
+                    format!(
+                        "a resolution of {} is too low, it must be at least {}",
+                        n, MIN_RESOLUTION
+                    ),
+                ),
+                Some(ResolutionMode::Tolerance(t)) if t <= 0.0 || t.is_nan() => diag.report(
+                    Code::InvalidResolution,
+                    span(i),
+                    format!("the tolerance must be more than 0 pixels, not {}", t),
+                ),
+                _ => (),
+            }
+            if let Some(knob) = com.knob() {
+                if has_frames && !all_given && !given.contains(&knob) {
+                    diag.report(
+                        Code::UnknownKnob,
+                        span(i),
+                        format!(
+                            "knob `{}` is never given a value with `vary`, `set`, or `tween`",
+                            knob.to_string()
+                        ),
                     );
-                } else {
-                    panic!("bad bad very bad, I literly filtered for only vary commands");
                 }
             }
+        }
+    }
 
-            let frame_array = (0..frames).into_par_iter().map(|f| {
-                //should be fine as eng should be close to empty
-                let eng = &mut eng.clone();
-                for com in self.commands.iter() {
-                    match com {
-                        Command::Push(c) => c.run(eng),
-                        Command::Pop(c) => c.run(eng),
-                        Command::Move(c) => c.go(eng, f),
-                        Command::Rotate(c) => c.go(eng, f),
-                        Command::Scale(c) => c.go(eng, f),
-                        Command::Box(c) => c.run(eng),
-                        Command::Sphere(c) => c.run(eng),
-                        Command::Torus(c) => c.run(eng),
-                        Command::Line(c) => c.run(eng),
-                        Command::Save(_) => (),
-                        Command::Display(_) => (),
-                        Command::Basename(_) => (),
-                        Command::Frames(_) => (),
-                        Command::Vary(_) => (),
-                        Command::End => (),
-                    }
+    ///runs the script, drawing to the engine's screen
+    ///every problem found is added to `diag`, which should be the one the script was parsed with
+    ///so problems are shown in the source, nothing is drawn if `diag` has any errors
+    pub fn exec<T: Color>(self, eng: &mut Engine<T>, diag: &mut Diagnostics) {
+        //scripts parsed from source were already checked, and their warnings reported, by
+        //`from_mdl`
+        if self.spans.is_empty() {
+            self.check(diag);
+        }
+        if diag.has_errors() {
+            return;
+        }
+
+        //meshes are read once up front, so a missing file stops the script before anything is
+        //drawn and the frames of an animation don't each read it again
+        for (i, com) in self.commands.iter().enumerate() {
+            if let Command::Mesh(MeshCommand { file, .. }) = com {
+                let file = file.to_string();
+                if let Err(e) = eng.load_mesh(&file) {
+                    diag.report(
+                        Code::MeshLoadFailed,
+                        self.spans.get(i).copied(),
+                        format!("cannot load mesh `{}`: {}", file, e),
+                    );
+                }
+            }
+        }
+        if diag.has_errors() {
+            return;
+        }
+
+        // if there are more than one frames/basenames commands, we take the last one stated
+        let frames = self.commands.iter().rev().find_map(|x| match x {
+            Command::Frames(FramesCommand { frames }) => Some(*frames),
+            _ => None,
+        });
+
+        //every knob starts at 1 so `setknobs` and `save_knobs` cover all of them, then the knob
+        //commands are run once in order to save the lists tweens go between, a knob used before
+        //any `set` in a frame has the last value it is set to
+        for com in &self.commands {
+            let knob = match com {
+                Command::Vary(VaryCommand { knob, .. })
+                | Command::Set(SetCommand { knob, .. })
+                | Command::Spline(SplineCommand { knob, .. }) => Some(knob),
+                com => com.knob(),
+            };
+            if let Some(knob) = knob {
+                eng.add_knob(&knob.to_string());
+            }
+        }
+        //`check` already found any ranges or lists the engine would refuse
+        for (i, com) in self.commands.iter().enumerate() {
+            let added = match (com, frames) {
+                (Command::Set(c), _) => {
+                    c.run(eng);
+                    Ok(())
                 }
-                let out = eng.ppm_byte_vec();
-                eng.clear_tris();
-                eng.clear_lines();
-                eng.clear_screen();
-                eng.clear_stack();
+                (Command::SetKnobs(c), _) => {
+                    c.run(eng);
+                    Ok(())
+                }
+                (Command::SaveKnobs(c), _) => {
+                    c.run(eng);
+                    Ok(())
+                }
+                (Command::Vary(c), Some(frames)) => eng.add_vary(
+                    c.knob.to_string(),
+                    c.start_frame,
+                    c.end_frame,
+                    c.start_val,
+                    c.end_val,
+                    c.easing.into(),
+                    frames,
+                ),
+                (Command::Spline(c), Some(_)) => {
+                    eng.add_spline(c.knob.to_string(), c.keys.spline())
+                }
+                (Command::Tween(c), Some(frames)) => eng.add_tween(
+                    c.start_frame,
+                    c.end_frame,
+                    &c.list0.to_string(),
+                    &c.list1.to_string(),
+                    frames,
+                ),
+                _ => Ok(()),
+            };
+            if let Err(e) = added {
+                let code = match e {
+                    KnobError::FrameRange { .. } => Code::InvalidFrameRange,
+                    KnobError::UnknownList(_) => Code::UnknownKnobList,
+                    KnobError::Spline(_) => Code::InvalidSpline,
+                };
+                diag.report(code, self.spans.get(i).copied(), e.to_string());
+            }
+        }
+        if diag.has_errors() {
+            return;
+        }
+
+        //lights shine on the whole scene no matter where they are declared, and a script that
+        //brings its own lights replaces any the engine already had
+        let lights: Vec<_> = self
+            .commands
+            .iter()
+            .filter_map(|x| match x {
+                Command::Light(l) => Some(l),
+                _ => None,
+            })
+            .collect();
+        if !lights.is_empty() {
+            eng.clear_lights();
+            lights.iter().for_each(|l| l.run(eng));
+        }
 
-                out
+        //ambient light is for the whole scene as well, the last one stated wins
+        let ambient = self.commands.iter().rev().find_map(|x| match x {
+            Command::Ambient(a) => Some(a),
+            _ => None,
+        });
+
+        //so is the camera
+        let camera = self.commands.iter().rev().find_map(|x| match x {
+            Command::Camera(c) => Some(c),
+            _ => None,
+        });
+        if let Some(c) = camera {
+            c.run(eng);
+        }
+        //and the focal length, which also turns on perspective
+        let focal = self.commands.iter().rev().find_map(|x| match x {
+            Command::Focal(f) => Some(f),
+            _ => None,
+        });
+        if let Some(f) = focal {
+            f.run(eng);
+        }
+
+        let basename = self
+            .commands
+            .iter()
+            .rev()
+            .find_map(|x| match x {
+                Command::Basename(BasenameCommand { basename }) => Some(basename.to_string()),
+                _ => None,
             })
-            .collect::<Vec<_>>();
-            let convert_command = format!("convert -delay 1.7 -loop 0 - {}.gif", basename);
-            let mut convert = process::Command::new("sh")
-                .args(["-c", &convert_command])
-                .stdin(Stdio::piped())
-                .spawn()
-                .expect("failed to spawn convert command");
-
-            frame_array.into_iter().for_each(|frame| {
-                convert.stdin.as_mut().unwrap().write_all(&frame).unwrap();
-            });
-            convert.wait().unwrap();
+            .unwrap_or_else(|| "generic_mdl_animation".to_string());
+
+        if let Some(frames) = frames {
+            //create an animation, the frames are quantized and compressed as they are drawn
+            let frame_array = (0..frames)
+                .into_par_iter()
+                .map(|f| {
+                    //should be fine as eng should be close to empty
+                    let eng = &mut eng.clone();
+                    if let Some(a) = ambient {
+                        a.go(eng, f);
+                    }
+                    for com in self.commands.iter() {
+                        match com {
+                            Command::Push(c) => c.run(eng),
+                            Command::Pop(c) => c.run(eng),
+                            Command::Move(c) => c.go(eng, f),
+                            Command::Rotate(c) => c.go(eng, f),
+                            Command::Scale(c) => c.go(eng, f),
+                            Command::Box(c) => c.run(eng),
+                            Command::Sphere(c) => c.run(eng),
+                            Command::Torus(c) => c.run(eng),
+                            Command::Line(c) => c.run(eng),
+                            Command::Save(_) => (),
+                            Command::Display(_) => (),
+                            Command::Basename(_) => (),
+                            Command::Frames(_) => (),
+                            Command::Vary(_) => (),
+                            Command::Light(_) => (),
+                            Command::Ambient(_) => (),
+                            Command::Camera(_) => (),
+                            Command::Focal(_) => (),
+                            Command::Shading(c) => c.run(eng),
+                            Command::Resolution(c) => c.run(eng),
+                            Command::Mesh(c) => c.run(eng),
+                            Command::SaveCoordSystem(c) => c.run(eng),
+                            Command::Set(c) => c.run(eng),
+                            Command::SetKnobs(c) => c.run(eng),
+                            Command::SaveKnobs(_) => (),
+                            Command::Tween(_) => (),
+                            Command::Spline(_) => (),
+                            Command::End => (),
+                        }
+                    }
+                    let out = eng.gif_frame();
+                    eng.clear_tris();
+                    eng.clear_lines();
+                    eng.clear_screen();
+                    eng.clear_stack();
+
+                    //only the first frame is exported, one mesh can't hold a whole animation
+                    let export = eng.take_export().filter(|_| f == 0);
+                    (out, export)
+                })
+                .collect::<Vec<_>>();
+            let (frame_array, exports): (Vec<_>, Vec<_>) = frame_array.into_iter().unzip();
+            if let (Some(all), Some(first)) = (eng.export(), exports.into_iter().flatten().next()) {
+                all.append(&first);
+            }
+            let mut gif = eng.gif();
+            frame_array.into_iter().for_each(|frame| gif.push(frame));
+            let file = format!("{}.gif", basename);
+            if let Err(e) = gif.save(&file) {
+                let i = self
+                    .commands
+                    .iter()
+                    .rposition(|x| matches!(x, Command::Basename(_)));
+                diag.report(
+                    Code::AnimationSaveFailed,
+                    i.and_then(|i| self.spans.get(i).copied()),
+                    format!("cannot save animation `{}`: {}", file, e),
+                );
+            }
         } else {
             //create a still image
-            for com in self.commands {
+            if let Some(a) = ambient {
+                a.go(eng, 0);
+            }
+            for com in self.commands.iter() {
                 match com {
                     Command::Push(c) => c.run(eng),
                     Command::Pop(c) => c.run(eng),
-                    Command::Move(c) => c.run(eng),
-                    Command::Rotate(c) => c.run(eng),
-                    Command::Scale(c) => c.run(eng),
+                    Command::Move(c) => c.go(eng, 0),
+                    Command::Rotate(c) => c.go(eng, 0),
+                    Command::Scale(c) => c.go(eng, 0),
                     Command::Box(c) => c.run(eng),
                     Command::Sphere(c) => c.run(eng),
                     Command::Torus(c) => c.run(eng),
                     Command::Line(c) => c.run(eng),
                     Command::Save(c) => c.run(eng),
                     Command::Display(c) => c.run(eng),
-                    Command::Basename(_) => {
-                        println!("you sure you want a basename in this script?")
-                    }
+                    Command::Basename(_) => (),
                     Command::Frames(_) => panic!("bad bad very bad this should be impossible"),
                     Command::Vary(_) => panic!("bad bad very bad this should be impossible"),
+                    Command::Light(_) => (),
+                    Command::Ambient(_) => (),
+                    Command::Camera(_) => (),
+                    Command::Focal(_) => (),
+                    Command::Shading(c) => c.run(eng),
+                    Command::Resolution(c) => c.run(eng),
+                    Command::Mesh(c) => c.run(eng),
+                    Command::SaveCoordSystem(c) => c.run(eng),
+                    Command::Set(c) => c.run(eng),
+                    Command::SetKnobs(c) => c.run(eng),
+                    Command::SaveKnobs(_) => (),
+                    Command::Tween(_) => panic!("bad bad very bad this should be impossible"),
+                    Command::Spline(_) => panic!("bad bad very bad this should be impossible"),
                     Command::End => (),
                 }
             }
@@ -50,9 +50,11 @@ All image creation commands will operate as follows:
 3. Render the object.
 4. Throw away the point list (if this is applicable in your implementation).
 
-sphere [constants] x y z r [coord_system]
+sphere [constants] [resolution steps|auto|tolerance px] x y z r [coord_system]
 
-torus [constants] x y z r0 r1  [coord_system]
+torus [constants] [resolution steps|auto|tolerance px] x y z r0 r1  [coord_system]
+			- the resolution, if given, is used for this shape
+			  instead of the one from the resolution command
 
 box [constants] x0 y0 z0 h w d [coord_system]
 			- x0 y0 z0 = one corner of the box
@@ -63,9 +65,11 @@ line [constants] x0 y0 z0 [coord_system0] x1 y1 z1 [coord_system1]
 			  in its own coordinate system.
 
 mesh [constants] :filename [coord_system]
-			- load a mesh or set of edges (in some format that
-			  you can specify) from a file into the pointlist 
-			  and or edge list directly.
+			- load a mesh of triangles from a file, the format
+			  is picked from the extension, .obj is Wavefront
+			  OBJ, .stl is STL in binary or text, and .ply is
+			  PLY in text or binary, faces with more than
+			  three corners are split into triangles
 
 Knobs/Animation
 ---------------
@@ -76,6 +80,10 @@ basename name		- sets the base filename to save under.
 			  save the images r01.miff, r02.miff etc.
 
 set knobname value	- sets a knobs value (in the symbol table).
+			  Every knob starts at 1. A knob with no vary,
+			  tween, or spline has the value it was last
+			  set to, a knob used before any set has the
+			  last value the script sets it to.
 
 save_knobs knoblist	- saves the current values of all knobs
 			  under the name "knoblist."
@@ -87,13 +95,52 @@ tween start_frame end_frame knoblist0 knoblist1
 			  interpolate the image using knoblist0 as
 			  the starting configuration and knoblist 2
 			  as the ending configuration.
+			  Both lists must be saved before the tween,
+			  and it needs a frames command like vary.
 
 frames num_frames	- How many frames to generate all together.
+			  They are saved together as basename.gif,
+			  each with its own palette of at most 256
+			  colors.
 
-vary knob start_frame end_frame start_val end_val
+vary knob start_frame end_frame start_val end_val [easing]
 			- vary a knob from start_val to end_val over
 			  the course of start_frame to end_frame
-setknobs value		- set all the knobs to value
+			  easing is how the values in between are
+			  spaced out, linear if it isn't given:
+			  linear, ease-in, ease-out, ease-in-out,
+			  cubic, sine, bounce, step [jumps], or
+			  cubic-bezier x1 y1 x2 y2 like in css
+			  the frames must be in the animation and
+			  the end can't come before the start.
+			  Before its first vary a knob has the value
+			  that vary starts at, after a vary it keeps
+			  the value it ended at until the next.
+			  Ranges of the same knob may share the frame
+			  one ends and the next starts on, if they
+			  overlap more there is a warning and the
+			  later vary is used where they overlap.
+spline knob kind keys...
+			- gives a knob a smooth curve through keys
+			  instead of straight ramps, kind is one of
+			  catmull-rom: keys are frame value, the
+			    curve's slope at each key is found from
+			    the keys on either side of it
+			  hermite: keys are frame value slope, the
+			    slope being change in value per frame
+			  bezier: frame value points, the curve
+			    goes through the first and every third
+			    one after it and is pulled towards the
+			    two between, which must be in the frames
+			    between the keys on either side of them
+			  the keys must be in order of frame and
+			  there must be at least 2. Before the first
+			  key the knob has the first value and after
+			  the last key the last. A knob with a vary
+			  or tween uses those instead.
+
+setknobs value		- set all the knobs to value, every knob the
+			  script mentions anywhere is included
 
 
 Lighting
@@ -102,7 +149,8 @@ light r g b x y z  	- creates a "light" datastructure with rgb values
 			  r,g,b at location x,y,z.
 			  This is inserted into the symbol table.
 
-ambient r g b 		- specifies how much ambient light is in the scene
+ambient r g b [knob] 	- specifies how much ambient light is in the scene
+			  the knob, if given, scales r g b over an animation
 
 constants name kar kdr ksr kag kdg ksg kab kdb ksb [r] [g] [b]
 			- saves a set of lighting components in the
@@ -113,6 +161,13 @@ constants name kar kdr ksr kag kdg ksg kab kdb ksb [r] [g] [b]
 shading wireframe|flat|gouraud|phong|raytrace
 			- set the shading mode
 
+resolution steps|auto|tolerance px
+			- how many steps around the spheres and tori
+			  after this are drawn with, auto picks them
+			  from how big each shape is on the screen,
+			  tolerance splits each shape until it is
+			  within px pixels of the real shape
+
 
 MISC
 ----
@@ -120,6 +175,8 @@ MISC
 save_coord_system name
 			- Makes a copy of the top of the stack and 
 			  saves it in the symbol table under "name."
+			  Shapes after it can be drawn in it by giving
+			  name as their coord_system.
 
 camera eye aim		- establishes a camera. Eye and aim are
 			  x y z triples.
@@ -131,7 +188,8 @@ save filename		- save the image in its current state under
 gereate_rayfiles	- Instruct the interpreter to generate source
 			  files for a ray tracer for each frame rendered.
 
-focal value		- set the focal length of the camera
+focal value		- set the focal length of the camera and draw with
+			  perspective, without it the projection is orthographic
 
 display			- display the current image on the screen
 
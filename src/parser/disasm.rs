@@ -0,0 +1,207 @@
+//!turns a script back into MDL-like text so it is possible to see what actually ended up in an
+//!intermediate file, the rust side of print_pcode.c
+
+use binrw::io::Cursor;
+use binrw::BinWrite;
+
+use super::format::HEADER_LEN;
+use super::{
+    Command, CoordSystem, EasingMode, Knob, ResolutionMode, Script, ShadingMode, ShapeResolution,
+    SplineKeys,
+};
+
+fn knob(k: &Knob) -> String {
+    match k {
+        Knob::Constant => String::new(),
+        Knob::Name(name) => format!(" {}", name.to_string()),
+    }
+}
+
+///the coordinate system a shape is drawn in, which comes after its numbers
+fn coord_system(c: &CoordSystem) -> String {
+    match c {
+        CoordSystem::Stack => String::new(),
+        CoordSystem::Name(name) => format!(" {}", name.to_string()),
+    }
+}
+
+fn axis(a: f64) -> String {
+    if a == 0.0 {
+        "x".into()
+    } else if a == 1.0 {
+        "y".into()
+    } else if a == 2.0 {
+        "z".into()
+    } else {
+        a.to_string()
+    }
+}
+
+fn resolution(r: &ResolutionMode) -> String {
+    match r {
+        ResolutionMode::Steps(n) => format!("resolution {}", n),
+        ResolutionMode::Auto => "resolution auto".into(),
+        ResolutionMode::Tolerance(t) => format!("resolution tolerance {}", t),
+    }
+}
+
+///the easing of a `vary`, which comes after its numbers, nothing if it is linear
+fn easing(e: &EasingMode) -> String {
+    match e {
+        EasingMode::Linear => String::new(),
+        EasingMode::EaseIn => " ease-in".into(),
+        EasingMode::EaseOut => " ease-out".into(),
+        EasingMode::EaseInOut => " ease-in-out".into(),
+        EasingMode::Cubic => " cubic".into(),
+        EasingMode::Sine => " sine".into(),
+        EasingMode::Bounce => " bounce".into(),
+        EasingMode::Step(n) => format!(" step {}", n),
+        EasingMode::CubicBezier([x1, y1, x2, y2]) => {
+            format!(" cubic-bezier {} {} {} {}", x1, y1, x2, y2)
+        }
+    }
+}
+
+///a shape's own resolution, which comes before its numbers
+fn shape_resolution(r: &ShapeResolution) -> String {
+    match r {
+        ShapeResolution::Engine => String::new(),
+        ShapeResolution::Own(r) => format!("{} ", resolution(r)),
+    }
+}
+
+///the lighting constants of a shape, stored as all the ambient then diffuse then specular
+fn constants(c: &[f64; 9]) -> String {
+    format!(
+        "ka {} {} {}  kd {} {} {}  ks {} {} {}",
+        c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7], c[8]
+    )
+}
+
+fn line(com: &Command) -> String {
+    match com {
+        Command::Push(_) => "push".into(),
+        Command::Pop(_) => "pop".into(),
+        Command::Move(c) => format!("move {} {} {}{}", c.x, c.y, c.z, knob(&c.knob)),
+        Command::Rotate(c) => format!("rotate {} {}{}", axis(c.axis), c.theta, knob(&c.knob)),
+        Command::Scale(c) => format!("scale {} {} {}{}", c.x, c.y, c.z, knob(&c.knob)),
+        Command::Box(c) => format!(
+            "box {} {} {} {} {} {}{}  // {}",
+            c.x,
+            c.y,
+            c.z,
+            c.h,
+            c.w,
+            c.d,
+            coord_system(&c.coord_system),
+            constants(&c.constants)
+        ),
+        Command::Sphere(c) => format!(
+            "sphere {}{} {} {} {}{}  // {}",
+            shape_resolution(&c.resolution),
+            c.x,
+            c.y,
+            c.z,
+            c.r,
+            coord_system(&c.coord_system),
+            constants(&c.constants)
+        ),
+        Command::Torus(c) => format!(
+            "torus {}{} {} {} {} {}{}  // {}",
+            shape_resolution(&c.resolution),
+            c.x,
+            c.y,
+            c.z,
+            c.r0,
+            c.r1,
+            coord_system(&c.coord_system),
+            constants(&c.constants)
+        ),
+        Command::Line(c) => format!(
+            "line {} {} {}{} {} {} {}{}",
+            c.x0,
+            c.y0,
+            c.z0,
+            coord_system(&c.coord_system0),
+            c.x1,
+            c.y1,
+            c.z1,
+            coord_system(&c.coord_system1)
+        ),
+        Command::Save(c) => format!("save {}", c.file.to_string()),
+        Command::Display(_) => "display".into(),
+        Command::Basename(c) => format!("basename {}", c.basename.to_string()),
+        Command::Frames(c) => format!("frames {}", c.frames),
+        Command::Vary(c) => format!(
+            "vary {} {} {} {} {}{}",
+            c.knob.to_string(),
+            c.start_frame,
+            c.end_frame,
+            c.start_val,
+            c.end_val,
+            easing(&c.easing)
+        ),
+        Command::Light(c) => format!("light {} {} {} {} {} {}", c.r, c.g, c.b, c.x, c.y, c.z),
+        Command::Ambient(c) => format!("ambient {} {} {}{}", c.r, c.g, c.b, knob(&c.knob)),
+        Command::Camera(c) => format!(
+            "camera {} {} {} {} {} {}",
+            c.eye_x, c.eye_y, c.eye_z, c.aim_x, c.aim_y, c.aim_z
+        ),
+        Command::Focal(c) => format!("focal {}", c.focal),
+        Command::Shading(c) => match c.mode {
+            ShadingMode::Wireframe => "shading wireframe".into(),
+            ShadingMode::Flat => "shading flat".into(),
+            ShadingMode::Gouraud => "shading gouraud".into(),
+            ShadingMode::Phong => "shading phong".into(),
+        },
+        Command::Resolution(c) => resolution(&c.mode),
+        Command::Mesh(c) => format!(
+            "mesh :{}{}  // {}",
+            c.file.to_string(),
+            coord_system(&c.coord_system),
+            constants(&c.constants)
+        ),
+        Command::SaveCoordSystem(c) => format!("save_coord_system {}", c.name.to_string()),
+        Command::Set(c) => format!("set {} {}", c.knob.to_string(), c.value),
+        Command::SetKnobs(c) => format!("setknobs {}", c.value),
+        Command::SaveKnobs(c) => format!("save_knobs {}", c.list.to_string()),
+        Command::Spline(c) => {
+            let (kind, numbers): (_, Vec<f64>) = match &c.keys {
+                SplineKeys::CatmullRom { keys, .. } => ("catmull-rom", keys.concat()),
+                SplineKeys::Hermite { keys, .. } => ("hermite", keys.concat()),
+                SplineKeys::Bezier { points, .. } => ("bezier", points.concat()),
+            };
+            let mut out = format!("spline {} {}", c.knob.to_string(), kind);
+            for n in numbers {
+                out.push_str(&format!(" {}", n));
+            }
+            out
+        }
+        Command::Tween(c) => format!(
+            "tween {} {} {} {}",
+            c.start_frame,
+            c.end_frame,
+            c.list0.to_string(),
+            c.list1.to_string()
+        ),
+        Command::End => "// end".into(),
+    }
+}
+
+impl Script {
+    ///the script as MDL-like text, one command per line
+    ///each line starts with the byte offset of the command in the intermediate file and constants
+    ///are shown in a comment since the name they were given in the source is gone
+    pub fn disassemble(&self) -> String {
+        let mut out = String::new();
+        let mut offset = HEADER_LEN;
+        for com in &self.commands {
+            out.push_str(&format!("{:#06x}  {}\n", offset, line(com)));
+            let mut bytes = Cursor::new(Vec::new());
+            com.write_to(&mut bytes)
+                .expect("failed to write command to memory");
+            offset += bytes.into_inner().len();
+        }
+        out
+    }
+}
@@ -0,0 +1,189 @@
+//!framing for the intermediate file, a header in front of the commands and a checksum after them
+//!so stale or damaged files are rejected instead of being misread
+
+use binrw::io::{Read, Seek, Write};
+use binrw::{BinRead, BinResult, BinWrite, ReadOptions, WriteOptions};
+
+use super::{Command, Script};
+
+///bytes every intermediate file starts with
+pub const FORMAT_MAGIC: [u8; 4] = *b"MDLi";
+
+///version of the intermediate format, this must go up whenever opcodes are added or the bytes of
+///an existing command change meaning so older engines refuse the file instead of misreading it
+pub const FORMAT_VERSION: u16 = 14;
+
+///the highest opcode this engine understands
+const LAST_OPCODE: u8 = 0x1B;
+
+///bytes before the first command, the magic, version, and length of the commands
+pub(super) const HEADER_LEN: usize = 10;
+
+///CRC-32 (the same one zip and png use) of some bytes
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn fail<T>(pos: u64, message: String) -> BinResult<T> {
+    Err(binrw::Error::AssertFail { pos, message })
+}
+
+fn cut_off<T>(pos: u64) -> BinResult<T> {
+    fail(
+        pos,
+        "the file ends too early, it was probably cut off".into(),
+    )
+}
+
+///turns a failure partway through the commands into something a person can act on
+///by this point the checksum matched so the bytes are what was written, just not understood
+fn command_error(body: &[u8], offset: u64, err: binrw::Error) -> binrw::Error {
+    let pos = match err.root_cause() {
+        binrw::Error::EnumErrors { pos, .. } | binrw::Error::NoVariantMatch { pos } => *pos,
+        _ if err.is_eof() => {
+            return binrw::Error::AssertFail {
+                pos: offset + body.len() as u64,
+                message: "the commands run past their length without an end command".into(),
+            }
+        }
+        _ => return err,
+    };
+    let op = body[pos as usize];
+    let message = if op <= LAST_OPCODE {
+        format!("command with opcode 0x{:02X} is malformed", op)
+    } else {
+        format!(
+            "unknown opcode 0x{:02X}, the file may need a newer version of the engine",
+            op
+        )
+    };
+    binrw::Error::AssertFail {
+        pos: offset + pos,
+        message,
+    }
+}
+
+impl BinRead for Script {
+    type Args = ();
+
+    fn read_options<R: Read + Seek>(
+        reader: &mut R,
+        options: &ReadOptions,
+        _: Self::Args,
+    ) -> BinResult<Self> {
+        let start = reader.stream_position()?;
+        let mut header = [0u8; HEADER_LEN];
+        let got = read_up_to(reader, &mut header)?;
+        if got < 4 || header[..4] != FORMAT_MAGIC {
+            //files from the old C front end start straight away with one of its opcodes
+            let message = if got > 0 && header[0] <= 0x0E {
+                "the file has no header, it was probably written by the old C front end and \
+                 needs to be compiled again"
+            } else {
+                "not an MDL intermediate file"
+            };
+            return fail(start, message.into());
+        }
+        if got < header.len() {
+            return cut_off(start + got as u64);
+        }
+
+        let mut fields = binrw::io::Cursor::new(&header[4..]);
+        let version = u16::read_options(&mut fields, options, ())?;
+        if version != FORMAT_VERSION {
+            return fail(
+                start + 4,
+                format!(
+                    "the file uses intermediate format version {} but this engine reads version {}, \
+                     compile the script again",
+                    version, FORMAT_VERSION
+                ),
+            );
+        }
+        let len = u32::read_options(&mut fields, options, ())? as usize;
+
+        //the body and checksum are checked before anything in them is trusted
+        let mut framed = header.to_vec();
+        framed.resize(header.len() + len + 4, 0);
+        let got = read_up_to(reader, &mut framed[header.len()..])?;
+        if got < len + 4 {
+            return cut_off(start + (header.len() + got) as u64);
+        }
+        let (contents, checksum) = framed.split_at(header.len() + len);
+        let checksum = u32::read_options(&mut binrw::io::Cursor::new(checksum), options, ())?;
+        let expected = crc32(contents);
+        if checksum != expected {
+            return fail(
+                start + contents.len() as u64,
+                format!(
+                    "checksum is 0x{:08X} but the contents give 0x{:08X}, the file is corrupt",
+                    checksum, expected
+                ),
+            );
+        }
+
+        let body = &contents[header.len()..];
+        let offset = start + header.len() as u64;
+        let mut cursor = binrw::io::Cursor::new(body);
+        let commands: Vec<Command> =
+            binrw::until(|com| *com == Command::End)(&mut cursor, options, ())
+                .map_err(|e| command_error(body, offset, e))?;
+        if cursor.position() as usize != body.len() {
+            return fail(
+                offset + cursor.position(),
+                "there are bytes after the end command".into(),
+            );
+        }
+
+        Ok(Self {
+            commands,
+            spans: Vec::new(),
+        })
+    }
+}
+
+///reads until `buf` is full or the reader runs out, returning how much was read
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> BinResult<usize> {
+    let mut got = 0;
+    while got < buf.len() {
+        match reader.read(&mut buf[got..])? {
+            0 => break,
+            n => got += n,
+        }
+    }
+    Ok(got)
+}
+
+impl BinWrite for Script {
+    type Args = ();
+
+    fn write_options<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        options: &WriteOptions,
+        _: Self::Args,
+    ) -> BinResult<()> {
+        let mut body = binrw::io::Cursor::new(Vec::new());
+        self.commands.write_options(&mut body, options, ())?;
+        let body = body.into_inner();
+
+        //written to memory first so the checksum can be taken over the exact bytes
+        let mut out = binrw::io::Cursor::new(Vec::new());
+        FORMAT_MAGIC.write_options(&mut out, options, ())?;
+        FORMAT_VERSION.write_options(&mut out, options, ())?;
+        (body.len() as u32).write_options(&mut out, options, ())?;
+        out.write_all(&body)?;
+        let out = out.into_inner();
+
+        writer.write_all(&out)?;
+        crc32(&out).write_options(writer, options, ())
+    }
+}
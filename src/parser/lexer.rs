@@ -0,0 +1,180 @@
+//!turns MDL source text into a list of tokens
+//!the rules follow mdl.l so scripts that worked with the old flex lexer lex the same way here
+
+use crate::diagnostic::{Code, Diagnostics, Span};
+
+///every reserved word in MDL
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(super) enum Keyword {
+    Light,
+    Constants,
+    SaveCoordSystem,
+    Camera,
+    Ambient,
+    Torus,
+    Sphere,
+    Box,
+    Line,
+    Mesh,
+    Texture,
+    Set,
+    Move,
+    Scale,
+    Rotate,
+    Basename,
+    SaveKnobs,
+    Tween,
+    Frames,
+    Vary,
+    Spline,
+    Push,
+    Pop,
+    Save,
+    GenerateRayfiles,
+    Shading,
+    Setknobs,
+    Focal,
+    Display,
+    Web,
+    Resolution,
+}
+
+impl Keyword {
+    fn from_word(word: &str) -> Option<Self> {
+        let kw = match word {
+            "light" => Self::Light,
+            "constants" => Self::Constants,
+            "save_coord_system" => Self::SaveCoordSystem,
+            "camera" => Self::Camera,
+            "ambient" => Self::Ambient,
+            "torus" => Self::Torus,
+            "sphere" => Self::Sphere,
+            "box" => Self::Box,
+            "line" => Self::Line,
+            "mesh" => Self::Mesh,
+            "texture" => Self::Texture,
+            "set" => Self::Set,
+            "move" => Self::Move,
+            "scale" => Self::Scale,
+            "rotate" => Self::Rotate,
+            "basename" => Self::Basename,
+            "save_knobs" => Self::SaveKnobs,
+            "tween" => Self::Tween,
+            "frames" => Self::Frames,
+            "vary" => Self::Vary,
+            "spline" => Self::Spline,
+            "push" => Self::Push,
+            "pop" => Self::Pop,
+            "save" => Self::Save,
+            "generate_rayfiles" => Self::GenerateRayfiles,
+            "shading" => Self::Shading,
+            "setknobs" => Self::Setknobs,
+            "focal" => Self::Focal,
+            "display" => Self::Display,
+            "web" => Self::Web,
+            "resolution" => Self::Resolution,
+            _ => return None,
+        };
+        Some(kw)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub(super) enum TokenKind {
+    Number(f64),
+    ///any word which is not a keyword, used for names, knobs, axes, and file names
+    Ident(String),
+    Keyword(Keyword),
+    Colon,
+}
+
+///a token along with where it is in the source
+#[derive(Clone, PartialEq, Debug)]
+pub(super) struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+fn is_word_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || c == '/'
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '/' | '-')
+}
+
+///splits the source into tokens, comments and whitespace are thrown away
+///characters that can't start a token are reported and skipped so lexing can carry on
+pub(super) fn lex(src: &str, diag: &mut Diagnostics) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    for (l, text) in src.lines().enumerate() {
+        let line = l + 1;
+        let chars: Vec<char> = text.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let column = i + 1;
+            if c.is_whitespace() {
+                i += 1;
+            } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+                //comment to the end of the line
+                break;
+            } else if c == ':' {
+                tokens.push(Token {
+                    kind: TokenKind::Colon,
+                    span: Span::new(line, column, 1),
+                });
+                i += 1;
+            } else if c.is_ascii_digit() || c == '-' || c == '.' {
+                let start = i;
+                if c == '-' {
+                    i += 1;
+                }
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+                if i < chars.len() && chars[i] == '.' {
+                    i += 1;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+                let num: String = chars[start..i].iter().collect();
+                let span = Span::new(line, column, i - start);
+                match num.parse::<f64>() {
+                    Ok(value) => tokens.push(Token {
+                        kind: TokenKind::Number(value),
+                        span,
+                    }),
+                    Err(_) => diag.report(
+                        Code::InvalidNumber,
+                        Some(span),
+                        format!("`{}` is not a number", num),
+                    ),
+                }
+            } else if is_word_start(c) {
+                let start = i;
+                while i < chars.len() && is_word_char(chars[i]) {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                let kind = match Keyword::from_word(&word) {
+                    Some(kw) => TokenKind::Keyword(kw),
+                    None => TokenKind::Ident(word),
+                };
+                tokens.push(Token {
+                    kind,
+                    span: Span::new(line, column, i - start),
+                });
+            } else {
+                diag.report(
+                    Code::UnexpectedCharacter,
+                    Some(Span::new(line, column, 1)),
+                    format!("unexpected character `{}`", c),
+                );
+                i += 1;
+            }
+        }
+    }
+    tokens
+}
@@ -0,0 +1,648 @@
+//!a recursive descent parser for MDL source, it follows the grammar in MDL.spec and mdl.y and
+//!produces the same commands the C front end would have written to the intermediate file
+
+use std::collections::HashMap;
+
+use binrw::NullString;
+
+use super::lexer::{self, Keyword, Token, TokenKind};
+use super::*;
+use crate::diagnostic::{Code, Diagnostic, Diagnostics, Span};
+
+///the order of the constants is
+///'Ka_r', 'Ka_g', 'Ka_b', 'Kd_r', 'Kd_g', 'Kd_b', 'Ks_r', 'Ks_g', 'Ks_b'
+const DEFAULT_CONSTANTS: [f64; 9] = [0.1, 0.1, 0.1, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5];
+
+type Parse<T> = Result<T, Diagnostic>;
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    constants: HashMap<String, [f64; 9]>,
+    diag: &'a mut Diagnostics,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&TokenKind> {
+        self.tokens.get(self.pos).map(|t| &t.kind)
+    }
+
+    ///span of the next token, or just past the end of the script if there is none
+    fn here(&self) -> Span {
+        match (self.tokens.get(self.pos), self.tokens.last()) {
+            (Some(t), _) => t.span,
+            (None, Some(t)) => Span::new(t.span.line, t.span.column + t.span.len, 1),
+            (None, None) => Span::new(1, 1, 1),
+        }
+    }
+
+    ///reports something missing, pointing just past the previous token when the next one is on
+    ///a later line since that is where the missing piece should have been
+    fn error<T>(&self, code: Code, message: String) -> Parse<T> {
+        let span = match (
+            self.pos.checked_sub(1).map(|p| &self.tokens[p]),
+            self.tokens.get(self.pos),
+        ) {
+            (Some(prev), Some(next)) if prev.span.line != next.span.line => {
+                Span::new(prev.span.line, prev.span.column + prev.span.len, 1)
+            }
+            _ => self.here(),
+        };
+        Err(Diagnostic::new(code, Some(span), message))
+    }
+
+    fn number(&mut self, what: &str) -> Parse<f64> {
+        match self.peek() {
+            Some(&TokenKind::Number(n)) => {
+                self.pos += 1;
+                Ok(n)
+            }
+            _ => self.error(
+                Code::ExpectedNumber,
+                format!("expected a number for {}", what),
+            ),
+        }
+    }
+
+    fn numbers<const N: usize>(&mut self, what: &str) -> Parse<[f64; N]> {
+        let mut out = [0.0; N];
+        for n in out.iter_mut() {
+            *n = self.number(what)?;
+        }
+        Ok(out)
+    }
+
+    fn frame(&mut self, what: &str) -> Parse<u32> {
+        let span = self.here();
+        let n = self.number(what)?;
+        if n < 0.0 || n > u32::MAX as f64 || n.fract() != 0.0 {
+            return Err(Diagnostic::new(
+                Code::InvalidFrame,
+                Some(span),
+                format!("{} must be a non-negative whole number, not {}", what, n),
+            ));
+        }
+        Ok(n as u32)
+    }
+
+    fn ident(&mut self, what: &str) -> Parse<String> {
+        match self.opt_ident() {
+            Some(s) => Ok(s),
+            None => self.error(Code::ExpectedName, format!("expected a name for {}", what)),
+        }
+    }
+
+    fn opt_ident(&mut self) -> Option<String> {
+        match self.peek() {
+            Some(TokenKind::Ident(s)) => {
+                let s = s.clone();
+                self.pos += 1;
+                Some(s)
+            }
+            _ => None,
+        }
+    }
+
+    fn opt_knob(&mut self) -> Knob {
+        match self.opt_ident() {
+            Some(name) => Knob::Name(NullString::from_string(name)),
+            None => Knob::Constant,
+        }
+    }
+
+    ///optional constants name at the start of a shape, falls back to the defaults
+    fn opt_constants(&mut self) -> Parse<[f64; 9]> {
+        let span = self.here();
+        match self.opt_ident() {
+            None => Ok(DEFAULT_CONSTANTS),
+            Some(name) => self.constants.get(&name).copied().ok_or_else(|| {
+                Diagnostic::new(
+                    Code::UndefinedConstants,
+                    Some(span),
+                    format!("constants `{}` are not defined", name),
+                )
+            }),
+        }
+    }
+
+    ///what follows the `resolution` keyword, a number of steps, `auto`, or `tolerance` and a
+    ///number of pixels
+    fn resolution_mode(&mut self) -> Parse<ResolutionMode> {
+        let span = self.here();
+        match self.peek() {
+            Some(TokenKind::Ident(s)) if s == "auto" => {
+                self.pos += 1;
+                Ok(ResolutionMode::Auto)
+            }
+            Some(TokenKind::Ident(s)) if s == "tolerance" => {
+                self.pos += 1;
+                let span = self.here();
+                let t = self.number("the tolerance")?;
+                if t <= 0.0 {
+                    return Err(Diagnostic::new(
+                        Code::InvalidResolution,
+                        Some(span),
+                        format!("the tolerance must be more than 0 pixels, not {}", t),
+                    ));
+                }
+                Ok(ResolutionMode::Tolerance(t))
+            }
+            Some(TokenKind::Number(_)) => {
+                let n = self.number("the resolution")?;
+                if n.fract() != 0.0 || n < MIN_RESOLUTION as f64 || n > u32::MAX as f64 {
+                    return Err(Diagnostic::new(
+                        Code::InvalidResolution,
+                        Some(span),
+                        format!(
+                            "the resolution must be a whole number of at least {}, not {}",
+                            MIN_RESOLUTION, n
+                        ),
+                    ));
+                }
+                Ok(ResolutionMode::Steps(n as u32))
+            }
+            _ => self.error(
+                Code::InvalidResolution,
+                "expected a number of steps, `auto`, or `tolerance` for the resolution".into(),
+            ),
+        }
+    }
+
+    ///optional resolution of a shape, between its constants and its numbers
+    fn opt_resolution(&mut self) -> Parse<ShapeResolution> {
+        if self.peek() != Some(&TokenKind::Keyword(Keyword::Resolution)) {
+            return Ok(ShapeResolution::Engine);
+        }
+        self.pos += 1;
+        Ok(ShapeResolution::Own(self.resolution_mode()?))
+    }
+
+    ///optional easing at the end of a `vary`, linear if there is none
+    fn opt_easing(&mut self) -> Parse<EasingMode> {
+        let span = self.here();
+        let name = match self.opt_ident() {
+            Some(name) => name,
+            None => return Ok(EasingMode::Linear),
+        };
+        Ok(match name.as_str() {
+            "linear" => EasingMode::Linear,
+            "ease-in" => EasingMode::EaseIn,
+            "ease-out" => EasingMode::EaseOut,
+            "ease-in-out" => EasingMode::EaseInOut,
+            "cubic" => EasingMode::Cubic,
+            "sine" => EasingMode::Sine,
+            "bounce" => EasingMode::Bounce,
+            "step" => match self.peek() {
+                Some(TokenKind::Number(_)) => EasingMode::Step(self.frame("the number of steps")?),
+                _ => EasingMode::Step(1),
+            },
+            "cubic-bezier" => EasingMode::CubicBezier(self.numbers("cubic-bezier")?),
+            _ => {
+                return Err(Diagnostic::new(
+                    Code::InvalidEasing,
+                    Some(span),
+                    format!(
+                        "`{}` is not an easing, expected linear, ease-in, ease-out, ease-in-out, \
+                         cubic, sine, bounce, step, or cubic-bezier",
+                        name
+                    ),
+                ))
+            }
+        })
+    }
+
+    ///optional coordinate system at the end of a shape
+    fn opt_coord_system(&mut self) -> CoordSystem {
+        match self.opt_ident() {
+            Some(name) => CoordSystem::Name(NullString::from_string(name)),
+            None => CoordSystem::Stack,
+        }
+    }
+
+    fn unsupported(&mut self, span: Span, name: &str) {
+        self.diag.report(
+            Code::Unsupported,
+            Some(span),
+            format!("`{}` is not supported yet and will be ignored", name),
+        );
+    }
+
+    ///span covering the tokens from `start` up to the current position
+    ///it stops at the end of the first line so a snippet never has to show more than one line
+    fn span_from(&self, start: usize) -> Span {
+        let first = self.tokens[start].span;
+        let last = self.tokens[start..self.pos]
+            .iter()
+            .take_while(|t| t.span.line == first.line)
+            .last()
+            .map_or(first, |t| t.span);
+        Span::new(
+            first.line,
+            first.column,
+            last.column + last.len - first.column,
+        )
+    }
+
+    ///parses one statement, returns None for statements that do not make a command
+    fn statement(&mut self) -> Parse<Option<Command>> {
+        let kw = match self.peek() {
+            Some(&TokenKind::Keyword(kw)) => kw,
+            Some(TokenKind::Number(n)) => {
+                return self.error(
+                    Code::ExpectedCommand,
+                    format!("expected a command, found the number {}", n),
+                )
+            }
+            Some(TokenKind::Ident(s)) => {
+                return self.error(
+                    Code::ExpectedCommand,
+                    format!("expected a command, found `{}`", s),
+                )
+            }
+            Some(TokenKind::Colon) => {
+                return self.error(
+                    Code::ExpectedCommand,
+                    "expected a command, found `:`".into(),
+                )
+            }
+            None => return Ok(None),
+        };
+        let kw_span = self.here();
+        self.pos += 1;
+
+        let com = match kw {
+            Keyword::Push => Command::Push(PushCommand {}),
+            Keyword::Pop => Command::Pop(PopCommand {}),
+            Keyword::Move => {
+                let [x, y, z] = self.numbers("move")?;
+                let knob = self.opt_knob();
+                Command::Move(MoveCommand { x, y, z, knob })
+            }
+            Keyword::Scale => {
+                let [x, y, z] = self.numbers("scale")?;
+                let knob = self.opt_knob();
+                Command::Scale(ScaleCommand { x, y, z, knob })
+            }
+            Keyword::Rotate => {
+                let span = self.here();
+                let axis = match self.ident("the rotation axis")?.as_str() {
+                    "x" | "X" => 0.0,
+                    "y" | "Y" => 1.0,
+                    "z" | "Z" => 2.0,
+                    a => {
+                        return Err(Diagnostic::new(
+                            Code::InvalidAxis,
+                            Some(span),
+                            format!("`{}` is not an axis, expected x, y, or z", a),
+                        ))
+                    }
+                };
+                let theta = self.number("rotate")?;
+                let knob = self.opt_knob();
+                Command::Rotate(RotateCommand { axis, theta, knob })
+            }
+            Keyword::Box => {
+                let constants = self.opt_constants()?;
+                let [x, y, z, h, w, d] = self.numbers("box")?;
+                let coord_system = self.opt_coord_system();
+                Command::Box(BoxCommand {
+                    x,
+                    y,
+                    z,
+                    h,
+                    w,
+                    d,
+                    constants,
+                    coord_system,
+                })
+            }
+            Keyword::Sphere => {
+                let constants = self.opt_constants()?;
+                let resolution = self.opt_resolution()?;
+                let [x, y, z, r] = self.numbers("sphere")?;
+                let coord_system = self.opt_coord_system();
+                Command::Sphere(SphereCommand {
+                    x,
+                    y,
+                    z,
+                    r,
+                    constants,
+                    resolution,
+                    coord_system,
+                })
+            }
+            Keyword::Torus => {
+                let constants = self.opt_constants()?;
+                let resolution = self.opt_resolution()?;
+                let [x, y, z, r0, r1] = self.numbers("torus")?;
+                let coord_system = self.opt_coord_system();
+                Command::Torus(TorusCommand {
+                    x,
+                    y,
+                    z,
+                    r0,
+                    r1,
+                    constants,
+                    resolution,
+                    coord_system,
+                })
+            }
+            Keyword::Line => {
+                //lines are not lit so their constants are only checked
+                self.opt_constants()?;
+                let [x0, y0, z0] = self.numbers("line")?;
+                let coord_system0 = self.opt_coord_system();
+                let [x1, y1, z1] = self.numbers("line")?;
+                let coord_system1 = self.opt_coord_system();
+                Command::Line(LineCommand {
+                    x0,
+                    y0,
+                    z0,
+                    x1,
+                    y1,
+                    z1,
+                    coord_system0,
+                    coord_system1,
+                })
+            }
+            Keyword::Mesh => {
+                let constants = self.opt_constants()?;
+                if self.peek() != Some(&TokenKind::Colon) {
+                    return self.error(
+                        Code::ExpectedColon,
+                        "expected `:` before the mesh file name".into(),
+                    );
+                }
+                self.pos += 1;
+                let file = self.ident("the mesh file")?;
+                let coord_system = self.opt_coord_system();
+                Command::Mesh(MeshCommand {
+                    file: NullString::from_string(file),
+                    constants,
+                    coord_system,
+                })
+            }
+            Keyword::Save => {
+                let file = self.ident("the file to save to")?;
+                Command::Save(SaveCommand {
+                    file: NullString::from_string(file),
+                })
+            }
+            Keyword::Display => Command::Display(DisplayCommand {}),
+            Keyword::Basename => {
+                let basename = self.ident("basename")?;
+                Command::Basename(BasenameCommand {
+                    basename: NullString::from_string(basename),
+                })
+            }
+            Keyword::Frames => {
+                let frames = self.frame("the number of frames")?;
+                Command::Frames(FramesCommand { frames })
+            }
+            Keyword::Vary => {
+                let knob = self.ident("the knob to vary")?;
+                let start_frame = self.frame("the start frame")?;
+                let end_frame = self.frame("the end frame")?;
+                let [start_val, end_val] = self.numbers("vary")?;
+                let easing = self.opt_easing()?;
+                Command::Vary(VaryCommand {
+                    knob: NullString::from_string(knob),
+                    start_frame,
+                    end_frame,
+                    start_val,
+                    end_val,
+                    easing,
+                })
+            }
+            Keyword::Spline => {
+                let knob = self.ident("the knob the spline is for")?;
+                let span = self.here();
+                let kind = self.ident("the kind of spline")?;
+                let per_key = match kind.as_str() {
+                    "catmull-rom" | "bezier" => 2,
+                    "hermite" => 3,
+                    _ => {
+                        return Err(Diagnostic::new(
+                            Code::InvalidSpline,
+                            Some(span),
+                            format!(
+                                "`{}` is not a kind of spline, expected catmull-rom, hermite, or \
+                                 bezier",
+                                kind
+                            ),
+                        ))
+                    }
+                };
+                let mut numbers = Vec::new();
+                while let Some(TokenKind::Number(_)) = self.peek() {
+                    numbers.push(self.number("the keys")?);
+                }
+                if numbers.len() % per_key != 0 || numbers.len() / per_key > u16::MAX as usize {
+                    return Err(Diagnostic::new(
+                        Code::InvalidSpline,
+                        Some(kw_span),
+                        format!(
+                            "each key of a {} spline is {} numbers and there can be at most {} \
+                             keys, found {} numbers",
+                            kind,
+                            per_key,
+                            u16::MAX,
+                            numbers.len()
+                        ),
+                    ));
+                }
+                let count = (numbers.len() / per_key) as u16;
+                let pairs = || numbers.chunks(2).map(|n| [n[0], n[1]]).collect();
+                let keys = match kind.as_str() {
+                    "hermite" => SplineKeys::Hermite {
+                        count,
+                        keys: numbers.chunks(3).map(|n| [n[0], n[1], n[2]]).collect(),
+                    },
+                    "bezier" => SplineKeys::Bezier {
+                        count,
+                        points: pairs(),
+                    },
+                    _ => SplineKeys::CatmullRom {
+                        count,
+                        keys: pairs(),
+                    },
+                };
+                Command::Spline(SplineCommand {
+                    knob: NullString::from_string(knob),
+                    keys,
+                })
+            }
+            Keyword::Constants => {
+                let name = self.ident("constants")?;
+                let k: [f64; 9] = self.numbers("constants")?;
+                //the optional r g b intensities are accepted but not used by the engine
+                if let Some(TokenKind::Number(_)) = self.peek() {
+                    self.numbers::<3>("constants")?;
+                }
+                //the script lists each color's ambient, diffuse, and specular together
+                let constants = [k[0], k[3], k[6], k[1], k[4], k[7], k[2], k[5], k[8]];
+                self.constants.insert(name, constants);
+                return Ok(None);
+            }
+            Keyword::Light => {
+                //mdl.y wants a name for the light but MDL.spec doesn't, so take either
+                self.opt_ident();
+                let [r, g, b, x, y, z] = self.numbers("light")?;
+                Command::Light(LightCommand { r, g, b, x, y, z })
+            }
+            Keyword::Ambient => {
+                let [r, g, b] = self.numbers("ambient")?;
+                let knob = self.opt_knob();
+                Command::Ambient(AmbientCommand { r, g, b, knob })
+            }
+            Keyword::Camera => {
+                let [eye_x, eye_y, eye_z, aim_x, aim_y, aim_z] = self.numbers("camera")?;
+                Command::Camera(CameraCommand {
+                    eye_x,
+                    eye_y,
+                    eye_z,
+                    aim_x,
+                    aim_y,
+                    aim_z,
+                })
+            }
+            Keyword::SaveCoordSystem => {
+                let name = self.ident("save_coord_system")?;
+                Command::SaveCoordSystem(SaveCoordSystemCommand {
+                    name: NullString::from_string(name),
+                })
+            }
+            Keyword::Texture => {
+                self.ident("texture")?;
+                self.numbers::<12>("texture")?;
+                self.unsupported(kw_span, "texture");
+                return Ok(None);
+            }
+            Keyword::Set => {
+                let knob = self.ident("the knob to set")?;
+                let value = self.number("set")?;
+                Command::Set(SetCommand {
+                    knob: NullString::from_string(knob),
+                    value,
+                })
+            }
+            Keyword::SaveKnobs => {
+                let list = self.ident("the knob list")?;
+                Command::SaveKnobs(SaveKnobsCommand {
+                    list: NullString::from_string(list),
+                })
+            }
+            Keyword::Tween => {
+                let start_frame = self.frame("the start frame")?;
+                let end_frame = self.frame("the end frame")?;
+                let list0 = self.ident("the first knob list")?;
+                let list1 = self.ident("the second knob list")?;
+                Command::Tween(TweenCommand {
+                    start_frame,
+                    end_frame,
+                    list0: NullString::from_string(list0),
+                    list1: NullString::from_string(list1),
+                })
+            }
+            Keyword::Setknobs => {
+                let value = self.number("setknobs")?;
+                Command::SetKnobs(SetKnobsCommand { value })
+            }
+            Keyword::Shading => {
+                let span = self.here();
+                let mode = match self.ident("the shading mode")?.as_str() {
+                    "wireframe" => ShadingMode::Wireframe,
+                    "flat" => ShadingMode::Flat,
+                    "gouraud" => ShadingMode::Gouraud,
+                    "phong" => ShadingMode::Phong,
+                    "raytrace" => {
+                        self.unsupported(kw_span, "shading raytrace");
+                        return Ok(None);
+                    }
+                    mode => {
+                        return Err(Diagnostic::new(
+                            Code::InvalidShading,
+                            Some(span),
+                            format!(
+                                "`{}` is not a shading mode, expected wireframe, flat, gouraud, phong, or raytrace",
+                                mode
+                            ),
+                        ))
+                    }
+                };
+                Command::Shading(ShadingCommand { mode })
+            }
+            Keyword::Focal => Command::Focal(FocalCommand {
+                focal: self.number("focal")?,
+            }),
+            Keyword::GenerateRayfiles => {
+                self.unsupported(kw_span, "generate_rayfiles");
+                return Ok(None);
+            }
+            Keyword::Web => {
+                self.unsupported(kw_span, "web");
+                return Ok(None);
+            }
+            Keyword::Resolution => Command::Resolution(ResolutionCommand {
+                mode: self.resolution_mode()?,
+            }),
+        };
+        Ok(Some(com))
+    }
+
+    ///skips to the start of the next statement so one mistake doesn't hide the ones after it
+    fn recover(&mut self, start: usize) {
+        if self.pos == start {
+            self.pos += 1;
+        }
+        while let Some(kind) = self.peek() {
+            if matches!(kind, TokenKind::Keyword(_)) {
+                break;
+            }
+            self.pos += 1;
+        }
+    }
+}
+
+impl Script {
+    ///parses MDL source text into a script ready to be run
+    ///every problem found is added to `diag`, if any of them are errors no script is returned
+    pub fn from_mdl(src: &str, diag: &mut Diagnostics) -> Option<Self> {
+        let tokens = lexer::lex(src, diag);
+        let end = tokens.last().map_or(Span::new(1, 1, 1), |t| {
+            Span::new(t.span.line, t.span.column + t.span.len, 1)
+        });
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            constants: HashMap::new(),
+            diag,
+        };
+        let mut commands = Vec::new();
+        let mut spans = Vec::new();
+        while parser.pos < parser.tokens.len() {
+            let start = parser.pos;
+            match parser.statement() {
+                Ok(Some(com)) => {
+                    commands.push(com);
+                    spans.push(parser.span_from(start));
+                }
+                Ok(None) => (),
+                Err(d) => {
+                    parser.diag.push(d);
+                    parser.recover(start);
+                }
+            }
+        }
+        commands.push(Command::End);
+        spans.push(end);
+
+        let script = Self { commands, spans };
+        script.check(diag);
+        if diag.has_errors() {
+            None
+        } else {
+            Some(script)
+        }
+    }
+}
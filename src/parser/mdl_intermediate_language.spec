@@ -4,6 +4,16 @@ It should be simple.
 Each command is composed of an op code followed by a constant amount of arguments.
 Each op code is one byte and depending on that op code there will be some more data. The size of the rest of the data will be specified in this spec. The following are the op codes which NEED to be handeled
 
+Every file starts with a header so stale or damaged files are refused instead of misread.
+The header is ten bytes:
+the four ascii bytes 'MDLi'
+a 16bit unsigned integer, the version of this format, currently 14
+this goes up whenever an op code is added or an existing command changes
+a 32bit unsigned integer, the number of bytes of commands that follow
+After the commands, which end with the 0x00 op code, is a 32bit unsigned integer holding the
+CRC-32 (the one zip and png use) of every byte before it, header included.
+Numbers are in the byte order of the machine, the same as the values inside the commands.
+
 --------------------------------------------------------
 0x01
 the "push" command
@@ -40,6 +50,9 @@ the first six values represent the corner of the box and the dimensions:
 'x0', 'y0', 'z0', 'h', 'w', 'd'
 The next nine reprsent lighting constants
 these are 'Ka_r', 'Ka_g', 'Ka_b', 'Kd_r', 'Kd_g', 'Kd_b', 'Ks_r', 'Ks_g', 'Ks_b'
+following these is the coordinate system of the box, one byte, 0x00 to draw it in the top of the
+stack, or 0x01 followed by a null terminated string, the name a coordinate system was saved under
+with the save_coord_system command
 
 0x07
 the simplist version of the sphere command
@@ -47,16 +60,23 @@ following the op code are thirteen f64 values
 the first three represent 'x', 'y', 'z', and 'r'
 the next nine are the lighting constants
 'Ka_r', 'Ka_g', 'Ka_b', 'Kd_r', 'Kd_g', 'Kd_b', 'Ks_r', 'Ks_g', 'Ks_b'
+following these is the resolution of the sphere, in the same form as for the torus
+then the coordinate system of the sphere, in the same form as for the box
 
 0x08
 the simplist version of the torus command following the op code are fourteen f64 values these are 'x', 'y', 'z', 'r0', 'r1'
 the next nine are the lighting constants
 'Ka_r', 'Ka_g', 'Ka_b', 'Kd_r', 'Kd_g', 'Kd_b', 'Ks_r', 'Ks_g', 'Ks_b'
+following these is the resolution of the torus, one byte, 0x00 to use the one set by the last
+resolution command, or 0x01 followed by a resolution in the same form as the resolution command
+then the coordinate system of the torus, in the same form as for the box
 
 0x09
 the simplist version of the line command
 following the op code are size f64 values representing
 the start and end points of the line: 'x0', 'y0', 'z0', 'x1', 'y1', 'z1'
+following these are the coordinate systems of the start then the end, each in the same form as
+for the box, so each end can be drawn in a different one
 
 0x0A
 this is the save command and should save an image as a png
@@ -82,3 +102,104 @@ the next two numbers are two 64bit floats between 0 and 1 representing
 how far along the animation starts into the transformation and at what point it ends
 0 means no transformation has been done, 1 means the entire transformation is completed
 first the start value then the end value
+last is one byte, the easing, which says how the values in between are spaced out
+0x00 linear, the same amount every frame
+0x01 ease-in, 0x02 ease-out, 0x03 ease-in-out, the css curves of the same names
+0x04 cubic, 0x05 sine, both slow at the start and end
+0x06 bounce, falls to the end value and bounces off it
+0x07 step, followed by a 32bit unsigned integer, how many jumps there are, at least 1
+0x08 cubic-bezier, followed by four f64 values 'x1', 'y1', 'x2', 'y2', the control points of a
+css cubic bezier, both x values must be from 0 to 1
+the end frame can't be before the start frame and must be less than the number of frames
+before its first vary a knob has the value that vary starts at, after a vary it keeps the value it
+ended at until the next, where the ranges of one knob overlap the later vary is used
+
+0x0F
+the light command, a point light shining on the whole scene no matter where it appears
+following the op code are six f64 values
+the first three are the color of the light 'r', 'g', 'b', each from 0 to 255
+the next three are where the light is 'x', 'y', 'z'
+if a script has any light commands they replace the light the engine would otherwise use
+
+0x10
+the ambient command, how much ambient light there is in the whole scene
+following the op code are three f64 values 'r', 'g', 'b', each from 0 to 255
+following these is one byte, 0x01 if there is a knob followed
+the by a null terminated string, or 0x00 if there is none
+the knob scales the color each frame, if there is more than one ambient command the last is used
+
+0x11
+the camera command
+following the op code are six f64 values
+the first three are where the eye is 'x', 'y', 'z', the next three are the point it looks at
+the point looked at ends up in the middle of the screen
+if there is more than one camera command the last is used
+
+0x12
+the focal command, switches from the default orthographic projection to perspective
+following the op code is one f64, the focal length
+anything that far from the eye is drawn at its own size
+without a camera command the eye is that far in front of the middle of the screen
+if there is more than one focal command the last is used
+
+0x13
+the shading command, how the shapes after it are colored in
+following the op code is one byte, 0x00 for wireframe, 0x01 for flat, 0x02 for gouraud, or 0x03
+for phong
+shapes before any shading command are flat shaded
+
+0x14
+the resolution command, how finely the spheres and tori after it are drawn
+following the op code is one byte, 0x00 followed by a 32bit unsigned integer, the number of steps
+around each shape which is at least 4, 0x01 to pick the steps from how big each shape is on the
+screen, or 0x02 followed by an f64, how many pixels at most any line or triangle may be from the
+real shape on the screen
+shapes before any resolution command use the engine's resolution, 500 steps unless it was told
+otherwise
+
+0x15
+the mesh command, draws the triangles of a mesh file
+following the op code is a null terminated string, the name of the file, then nine f64 values,
+the constants in the same order as the other shapes, then the coordinate system of the mesh, in
+the same form as for the box
+the format of the file is picked from its extension, every mesh is read before the script is
+run so a file which can't be read stops the script before anything is drawn
+
+0x16
+the save_coord_system command, saves a copy of the top of the stack as it is at this point
+following the op code is a null terminated string, the name it is saved under
+shapes after it can be drawn in it by name, saving under a name again replaces what was saved
+
+0x17
+the set command, sets a knob which has no vary, tween, or spline
+following the op code is a null terminated string, the knob, then one f64, its value
+knobs are set in the order the commands appear, every knob starts at 1
+
+0x18
+the setknobs command, sets every knob the script mentions
+following the op code is one f64, the value
+
+0x19
+the save_knobs command, saves the value every knob has at this point
+following the op code is a null terminated string, the name of the list
+saving under a name again replaces what was saved
+
+0x1A
+the tween command, moves every knob from its value in one saved list to its value in another
+following the op code are two 32bit unsigned integers, the first and last frame of the tween,
+then two null terminated strings, the list at the first frame and the list at the last frame
+both lists must be saved before the tween, which needs a frames command and holds values
+outside its frames like vary
+
+0x1B
+the spline command, gives a knob a smooth curve through keyframes
+following the op code is a null terminated string, the knob, then one byte, the kind of spline
+0x00 catmull-rom, 0x01 hermite, or 0x02 bezier
+after that is a 16bit unsigned integer, the number of keys, then the keys
+each catmull-rom key is two f64 values, the frame and the value
+each hermite key is three f64 values, the frame, the value, and the slope in value per frame
+each bezier key is two f64 values, a frame and a value, the curve goes through the first and every
+third one after it, there must be one more than a multiple of 3
+keys must be in order of frame, there must be at least 2, and bezier control points must be in
+the frames between the keys on either side of them
+a knob with a vary or tween uses those instead of the spline
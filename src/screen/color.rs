@@ -24,6 +24,9 @@ pub trait Color: Debug + Default + Clone + Copy + Add + AddAssign + Sub + Send +
     ///generally larger numbers in that tuple should mean a brighter color
     ///for rgb this means reds, greens, and blues, are brighter
     fn mult(&self, _: (Float, Float, Float)) -> Self;
+    ///makes a color from red, green, and blue values between 0 and `max_val`
+    ///values outside that range are clamped
+    fn from_rgb(_: (Float, Float, Float)) -> Self;
 }
 
 ///Color implemented in the common 8 bit RGB triple format.
@@ -34,6 +37,8 @@ pub struct RGB8Color {
     blue: u8,
 }
 
+//Uint may be wider than u8, so the conversions below are not always no-ops
+#[allow(clippy::useless_conversion)]
 impl Color for RGB8Color {
     fn red(&self) -> Uint {
         self.red.into()
@@ -66,6 +71,14 @@ impl Color for RGB8Color {
 
         Self { red, green, blue }
     }
+    fn from_rgb(c: (Float, Float, Float)) -> Self {
+        let clamp = |v: Float| v.min(Self::max_val() as Float).max(0.0) as u8;
+        Self {
+            red: clamp(c.0),
+            green: clamp(c.1),
+            blue: clamp(c.2),
+        }
+    }
 }
 
 impl From<(u8, u8, u8)> for RGB8Color {
@@ -84,6 +97,7 @@ impl AddAssign<Self> for RGB8Color {
     }
 }
 
+#[allow(clippy::unnecessary_cast)]
 impl Add<Self> for RGB8Color {
     type Output = Self;
 
@@ -99,6 +113,7 @@ impl Add<Self> for RGB8Color {
     }
 }
 
+#[allow(clippy::unnecessary_cast)]
 impl Sub<Self> for RGB8Color {
     type Output = Self;
 
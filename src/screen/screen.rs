@@ -51,12 +51,17 @@ impl<T: Color> Screen<T> {
     }
 
     fn plot(&mut self, x: i32, y: i32, z: Float, color: T) {
+        self.plot_with(x, y, z, || color);
+    }
+
+    ///like `plot` but the color is only worked out if the pixel will actually be drawn
+    fn plot_with(&mut self, x: i32, y: i32, z: Float, color: impl FnOnce() -> T) {
         if x >= 0 && y >= 0 {
             let cx = x as usize;
             let cy = y as usize;
             if cx < self.width() && cy < self.height() && self.zbuffer[cy][cx] - z < Z_RESOLUTION {
                 self.zbuffer[cy][cx] = z;
-                self.grid[cy][cx] = color;
+                self.grid[cy][cx] = color();
             }
         }
     }
@@ -110,180 +115,97 @@ impl<T: Color> Screen<T> {
 
     ///Draws a triangle of pixels to the screen
     pub fn draw_tri(&mut self, p1: Point, p2: Point, p3: Point, color: T) {
-        let (mut tt, mut tm, mut tb) = (p1, p2, p3);
-        if tm.1 > tt.1 {
+        self.draw_tri_with([(p1, []), (p2, []), (p3, [])], |_| color);
+    }
+
+    ///Draws a triangle of pixels to the screen, each corner comes with some values which are
+    ///interpolated across the triangle. `shade` is given the values at each pixel to pick its
+    ///color, so this can do things like blend colors or normals from the corners.
+    pub fn draw_tri_with<const N: usize>(
+        &mut self,
+        corners: [(Point, [Float; N]); 3],
+        shade: impl Fn([Float; N]) -> T,
+    ) {
+        let [mut tt, mut tm, mut tb] = corners;
+        if tm.0 .1 > tt.0 .1 {
             (tt, tm) = (tm, tt);
         }
-        if tb.1 > tm.1 {
+        if tb.0 .1 > tm.0 .1 {
             (tm, tb) = (tb, tm);
         }
-        if tm.1 > tt.1 {
+        if tm.0 .1 > tt.0 .1 {
             (tt, tm) = (tm, tt);
         }
 
         //implementation based off of dw's to have less error
-        let mut x0 = tb.0;
-        let mut x1 = tb.0;
-        let mut z0 = tb.2;
-        let mut z1 = tb.2;
-        let mut y = tb.1 as i32;
-
-        let dist0 = tt.1 as i32 - y + 1;
-        let dist1 = tm.1 as i32 - y + 1;
-        let dist2 = tt.1 as i32 - tm.1 as i32 + 1;
-
-        let dx0 = if dist0 > 0 {
-            (tt.0 - tb.0) / dist0 as Float
-        } else {
-            0.0
+        //an edge is walked by its x, z, and values, stepping once per scanline
+        type Edge<const N: usize> = (Float, Float, [Float; N]);
+        let start = |c: &(Point, [Float; N])| -> Edge<N> { (c.0 .0, c.0 .2, c.1) };
+        let step = |from: &(Point, [Float; N]), to: &(Point, [Float; N]), dist: i32| -> Edge<N> {
+            if dist > 0 {
+                let d = dist as Float;
+                (
+                    (to.0 .0 - from.0 .0) / d,
+                    (to.0 .2 - from.0 .2) / d,
+                    std::array::from_fn(|i| (to.1[i] - from.1[i]) / d),
+                )
+            } else {
+                (0.0, 0.0, [0.0; N])
+            }
         };
-        let mut dx1 = if dist1 > 0 {
-            (tm.0 - tb.0) / dist1 as Float
-        } else {
-            0.0
+        let advance = |e: &mut Edge<N>, d: &Edge<N>| {
+            e.0 += d.0;
+            e.1 += d.1;
+            e.2.iter_mut().zip(d.2).for_each(|(v, dv)| *v += dv);
         };
 
-        let dz0 = if dist0 > 0 {
-            (tt.2 - tb.2) / dist0 as Float
-        } else {
-            0.0
-        };
-        let mut dz1 = if dist1 > 0 {
-            (tm.2 - tb.2) / dist1 as Float
-        } else {
-            0.0
-        };
+        let mut y = tb.0 .1 as i32;
+        let dist0 = tt.0 .1 as i32 - y + 1;
+        let dist1 = tm.0 .1 as i32 - y + 1;
+        let dist2 = tt.0 .1 as i32 - tm.0 .1 as i32 + 1;
+
+        let d0 = step(&tb, &tt, dist0);
+        let mut d1 = step(&tb, &tm, dist1);
+        let mut e0 = start(&tb);
+        let mut e1 = start(&tb);
 
         let mut flip = false;
-        while y <= tt.1 as i32 {
-            if !flip && y >= tm.1 as i32 {
+        while y <= tt.0 .1 as i32 {
+            if !flip && y >= tm.0 .1 as i32 {
                 flip = true;
-                dx1 = if dist2 > 0 {
-                    (tt.0 - tm.0) / dist2 as Float
-                } else {
-                    0.0
-                };
-                dz1 = if dist2 > 0 {
-                    (tt.2 - tm.2) / dist2 as Float
-                } else {
-                    0.0
-                };
-                x1 = tm.0;
-                z1 = tm.2;
+                d1 = step(&tm, &tt, dist2);
+                e1 = start(&tm);
             }
 
-            let (x_start, x_end, z_start, z_end);
-            if x0 > x1 {
-                x_start = x1 as i32;
-                x_end = x0 as i32;
-                z_start = z1;
-                z_end = z0;
-            } else {
-                x_start = x0 as i32;
-                x_end = x1 as i32;
-                z_start = z0;
-                z_end = z1;
+            let (l, r) = if e0.0 > e1.0 { (e1, e0) } else { (e0, e1) };
+            let (x_start, x_end) = (l.0 as i32, r.0 as i32);
+            let mut d = (0.0, 0.0, [0.0; N]);
+            if x_end - x_start != 0 {
+                let n = (x_end - x_start + 1) as Float;
+                d = (
+                    0.0,
+                    (r.1 - l.1) / n,
+                    std::array::from_fn(|i| (r.2[i] - l.2[i]) / n),
+                );
             }
 
-            let delta_z = if x_end - x_start != 0 {
-                (z_end - z_start) / (x_end - x_start + 1) as Float
-            } else {
-                0.0
-            };
-
-            let mut z = z_start;
-            let mut x = x_start;
-            while x <= x_end {
-                self.plot(x, y, z, color);
-                z += delta_z;
-                x += 1;
+            let mut cur = l;
+            for x in x_start..=x_end {
+                self.plot_with(x, y, cur.1, || shade(cur.2));
+                advance(&mut cur, &d);
             }
 
-            x0 += dx0;
-            x1 += dx1;
-            z0 += dz0;
-            z1 += dz1;
+            advance(&mut e0, &d0);
+            advance(&mut e1, &d1);
             y += 1;
         }
-        //the scanline conversion algo used is a accuarate version by Yusuf
-        //the implementation is by me
-        //Yusuf, I think your code might be wrong
-        /*
-        let (mut tt, mut tm, mut tb) = (p1, p2, p3);
-        if tm.1 > tt.1 {
-            (tt, tm) = (tm, tt);
-        }
-        if tb.1 > tm.1 {
-            (tm, tb) = (tb, tm);
-        }
-        if tm.1 > tt.1 {
-            (tt, tm) = (tm, tt);
-        }
-
-        let dxbm = (tm.0 - tb.0) / (tm.1 - tb.1);
-        let dzbm = (tm.2 - tb.2) / (tm.1 - tb.1);
-        let dxmt = (tt.0 - tm.0) / (tt.1 - tm.1);
-        let dzmt = (tt.2 - tm.2) / (tt.1 - tm.1);
-        let dxbt = (tt.0 - tb.0) / (tt.1 - tb.1);
-        let dzbt = (tt.2 - tb.2) / (tt.1 - tb.1);
-
-        let left_to_right = dxbm < dxbt;
-
-        let mut x0 = tb.0 + dxbm * (tb.1.ceil() - tb.1);
-        let mut z0 = tb.2 + dzbm * (tb.1.ceil() - tb.1);
-
-        let mut x1 = tb.0 + dxbt * (tb.1.ceil() - tb.1);
-        let mut z1 = tb.2 + dzbt * (tb.1.ceil() - tb.1);
-
-        for y in tb.1.ceil() as i32..=tm.1.floor() as i32 {
-            if left_to_right {
-                let dz = (z1 - z0) / (x1.floor() - x0.ceil() + 1.0);
-                let mut z = z0;
-                for x in x0.ceil() as i32..=x1.floor() as i32 {
-                    self.plot(x, y, z, color);
-                    z += dz;
-                }
-            } else {
-                let dz = (z0 - z1) / (x0.floor() - x1.ceil() + 1.0);
-                let mut z = z1;
-                for x in x1.ceil() as i32..=x0.floor() as i32 {
-                    self.plot(x, y, z, color);
-                    z += dz;
-                }
-            }
-            x0 += dxbm;
-            z0 += dzbm;
-            x1 += dxbt;
-            z1 += dzbt;
-        }
-
-        x0 = tm.0 + dxmt * (tm.1.ceil() - tm.1);
-        z0 = tm.2 + dzmt * (tm.1.ceil() - tm.1);
-
-        for y in tm.1.ceil() as i32..=tt.1.floor() as i32 {
-            if left_to_right {
-                let dz = (z1 - z0) / (x1.floor() - x0.ceil() + 1.0);
-                let mut z = z0;
-                for x in x0.ceil() as i32..=x1.floor() as i32 {
-                    self.plot(x, y, z, color);
-                    z += dz;
-                }
-            } else {
-                let dz = (z0 - z1) / (x0.floor() - x1.ceil() + 1.0);
-                let mut z = z1;
-                for x in x1.ceil() as i32..=x0.floor() as i32 {
-                    self.plot(x, y, z, color);
-                    z += dz;
-                }
-            }
-            x0 += dxmt;
-            z0 += dzmt;
-            x1 += dxbt;
-            z1 += dzbt;
-        }
-    */
     }
 
+    #[allow(
+        unused_comparisons,
+        clippy::absurd_extreme_comparisons,
+        clippy::unnecessary_cast
+    )]
     pub fn byte_vec(&self) -> Vec<u8> {
         let mut out = Vec::new();
         let max_val = T::max_val();
@@ -310,6 +232,23 @@ impl<T: Color> Screen<T> {
         out
     }
 
+    ///the pixels with the top row first, 3 bytes each for red, green, and blue, scaled to 255
+    pub fn rgb_vec(&self) -> Vec<u8> {
+        let max_val = u32::from(T::max_val());
+        let scale = |v: u32| (v * 255 / max_val) as u8;
+        let mut out = Vec::with_capacity(self.width * self.height * 3);
+        for v in self.grid.iter().rev() {
+            for c in v {
+                out.extend_from_slice(&[
+                    scale(c.red().into()),
+                    scale(c.green().into()),
+                    scale(c.blue().into()),
+                ]);
+            }
+        }
+        out
+    }
+
     ///Write contents as ppm to specified file path.
     ///The header writes in the binary format
     pub fn write_binary_ppm(&self, file: &mut File) -> Result<(), io::Error> {
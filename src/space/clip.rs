@@ -0,0 +1,109 @@
+//!clipping of lines and polygons against planes, used to cut away whatever is outside the view
+//!volume before it is drawn so nothing behind the eye is projected and nothing far off the screen
+//!is scanned
+use super::{Float, Point};
+use crate::gmath;
+
+///one side of a plane, a point `p` is inside when `dot(normal, p) + offset` is not negative
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Plane {
+    pub normal: Point,
+    pub offset: Float,
+}
+
+impl Plane {
+    pub fn new(normal: Point, offset: Float) -> Self {
+        Self { normal, offset }
+    }
+
+    ///how far inside the plane a point is, scaled by the length of the normal
+    pub fn distance(&self, p: Point) -> Float {
+        gmath::dot(self.normal, p) + self.offset
+    }
+}
+
+fn lerp(p: Point, q: Point, t: Float) -> Point {
+    gmath::add(p, gmath::scale(t, gmath::sub(q, p)))
+}
+
+///something with a position that can be clipped, anything else it carries is blended along with
+///the position where it gets cut
+pub trait Vertex: Copy {
+    fn position(&self) -> Point;
+    ///the vertex `t` of the way from this one to `other`
+    fn lerp(&self, other: &Self, t: Float) -> Self;
+}
+
+impl Vertex for Point {
+    fn position(&self) -> Point {
+        *self
+    }
+
+    fn lerp(&self, other: &Self, t: Float) -> Self {
+        lerp(*self, *other, t)
+    }
+}
+
+///a position with some values like a color or normal
+impl<const N: usize> Vertex for (Point, [Float; N]) {
+    fn position(&self) -> Point {
+        self.0
+    }
+
+    fn lerp(&self, other: &Self, t: Float) -> Self {
+        (
+            lerp(self.0, other.0, t),
+            std::array::from_fn(|i| self.1[i] + t * (other.1[i] - self.1[i])),
+        )
+    }
+}
+
+///clips a convex polygon against every plane with the Sutherland–Hodgman algorithm
+///the vertices come back in the same order, and unchanged if nothing was cut off
+///if the polygon is entirely outside, the result is empty
+pub fn clip_polygon<V: Vertex>(poly: &[V], planes: &[Plane]) -> Vec<V> {
+    let mut out = poly.to_vec();
+    for plane in planes {
+        if out.is_empty() {
+            break;
+        }
+        let input = std::mem::take(&mut out);
+        let mut prev = input[input.len() - 1];
+        let mut prev_d = plane.distance(prev.position());
+        for &cur in &input {
+            let cur_d = plane.distance(cur.position());
+            if (cur_d >= 0.0) != (prev_d >= 0.0) {
+                out.push(prev.lerp(&cur, prev_d / (prev_d - cur_d)));
+            }
+            if cur_d >= 0.0 {
+                out.push(cur);
+            }
+            prev = cur;
+            prev_d = cur_d;
+        }
+    }
+    out
+}
+
+///clips a line segment against every plane, None if none of it is inside
+pub fn clip_line(p: Point, q: Point, planes: &[Plane]) -> Option<(Point, Point)> {
+    let (mut t0, mut t1) = (0.0, 1.0);
+    for plane in planes {
+        let (dp, dq) = (plane.distance(p), plane.distance(q));
+        if dp < 0.0 && dq < 0.0 {
+            return None;
+        }
+        if dp < 0.0 {
+            t0 = Float::max(t0, dp / (dp - dq));
+        } else if dq < 0.0 {
+            t1 = Float::min(t1, dp / (dp - dq));
+        }
+    }
+    if t0 > t1 {
+        return None;
+    }
+    //ends which weren't cut keep their exact values
+    let a = if t0 > 0.0 { lerp(p, q, t0) } else { p };
+    let b = if t1 < 1.0 { lerp(p, q, t1) } else { q };
+    Some((a, b))
+}
@@ -1,12 +1,14 @@
 //!a collection of structs dealing with spaces to put shapes in.
 
+pub mod clip;
 mod light;
 mod modtrix;
+#[allow(clippy::module_inception)]
 mod space;
 
 pub use light::Light;
 pub use modtrix::{move_matrix, rotx_matrix, roty_matrix, rotz_matrix, scale_matrix, Modtrix};
-pub use space::{draw_space, Space};
+pub use space::{draw_space, Projection, Shading, Space};
 
 //when Float is updated, make sure to update the below three lines as well
 pub type Float = f64;
@@ -1,4 +1,5 @@
-use super::Float;
+use super::{Float, Point};
+use crate::gmath;
 ///This is in actuality just a 4x4 matrix, but it is stored a bit differently and has the purpose
 ///of being used to transform a space.
 use std::fmt::{self, Debug};
@@ -44,6 +45,75 @@ impl Modtrix {
         }
         lhs.store = res;
     }
+
+    ///the view matrix for a camera at `eye` looking at `aim`
+    ///it moves the eye to the origin and turns the scene so the eye looks down the negative z
+    ///axis with positive y up, as close as it can be to up in the scene
+    pub fn look_at(eye: Point, aim: Point) -> Self {
+        use gmath::{cross, dot, normalize, sub};
+
+        let back = normalize(sub(eye, aim));
+        //looking straight up or down there is no sensible up, so pick one
+        let up = if back.0 == 0.0 && back.2 == 0.0 {
+            (0.0, 0.0, -back.1)
+        } else {
+            (0.0, 1.0, 0.0)
+        };
+        let right = normalize(cross(up, back));
+        let up = cross(back, right);
+        Modtrix::from([
+            [right.0, right.1, right.2, -dot(right, eye)],
+            [up.0, up.1, up.2, -dot(up, eye)],
+            [back.0, back.1, back.2, -dot(back, eye)],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    ///the matrix to transform normals by so they stay at right angles to the surface, the inverse
+    ///of the transpose of the rotating and scaling part of this matrix
+    ///a matrix which flattens everything has no inverse and leaves the normals alone
+    pub fn normal_matrix(&self) -> Self {
+        let m = &self.store;
+        //the cofactor of each entry in the top left 3x3, the inverse transpose is these over the
+        //determinant
+        let c = |i: usize, j: usize| {
+            let (r0, r1) = ((i + 1) % 3, (i + 2) % 3);
+            let (c0, c1) = ((j + 1) % 3, (j + 2) % 3);
+            m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+        };
+        let det = m[0][0] * c(0, 0) + m[0][1] * c(0, 1) + m[0][2] * c(0, 2);
+        if det == 0.0 {
+            return Modtrix::IDENT;
+        }
+        Modtrix::from([
+            [c(0, 0) / det, c(0, 1) / det, c(0, 2) / det, 0.0],
+            [c(1, 0) / det, c(1, 1) / det, c(1, 2) / det, 0.0],
+            [c(2, 0) / det, c(2, 1) / det, c(2, 2) / det, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    ///about the most this matrix stretches anything, the length of the longest axis after the
+    ///transformation
+    pub fn max_scale(&self) -> Float {
+        let s = &self.store;
+        (0..3)
+            .map(|c| {
+                let axis = (s[0][c], s[1][c], s[2][c]);
+                gmath::dot(axis, axis).sqrt()
+            })
+            .fold(0.0, Float::max)
+    }
+
+    ///transforms a single point
+    pub fn transform(&self, p: Point) -> Point {
+        let s = &self.store;
+        (
+            s[0][0] * p.0 + s[0][1] * p.1 + s[0][2] * p.2 + s[0][3],
+            s[1][0] * p.0 + s[1][1] * p.1 + s[1][2] * p.2 + s[1][3],
+            s[2][0] * p.0 + s[2][1] * p.1 + s[2][2] * p.2 + s[2][3],
+        )
+    }
 }
 
 impl From<[[Float; 4]; 4]> for Modtrix {
@@ -1,22 +1,62 @@
 ///A space where you can add lines and triangles and lights
 ///you write its stuff to a screen
+use std::collections::HashMap;
 use std::fmt::Debug;
-use std::sync::Arc;
 
+use super::clip::{self, Plane};
 use super::{Float, Light, Modtrix, Point};
 use crate::gmath;
 use crate::screen::{Color, Screen};
 
+///how the space is flattened onto the screen
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Projection {
+    ///straight onto the screen, things are the same size no matter how far away they are
+    Orthographic,
+    ///towards the eye, anything the given focal length away from the eye is drawn at its own size
+    Perspective(Float),
+}
+
+///how triangles are colored in
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Shading {
+    ///only the edges of each triangle are drawn, including the ones facing away
+    Wireframe,
+    ///every triangle is lit once and filled with a single color
+    Flat,
+    ///every corner is lit and the colors are blended across the triangle
+    Gouraud,
+    ///the normals at the corners are blended across the triangle and every pixel is lit
+    Phong,
+}
+
 #[derive(Clone, Debug)]
 pub struct Space<T: Color> {
     lin_space: Vec<[Float; 4]>,
-    tri_space: Vec<[Float; 4]>,
+    ///the corners of the triangles, shared between every triangle which uses them so each one is
+    ///only transformed once
+    vertices: Vec<[Float; 4]>,
+    ///a normal for each point in `vertices`, all zeros when the point was added without one
+    normals: Vec<[Float; 4]>,
+    ///indices into `vertices` for the corners of each triangle
+    tris: Vec<[u32; 3]>,
     lights: Vec<Light<T>>,
     ambient_light: T,
     ambient_reflection: (Float, Float, Float),
     diffuse_reflection: (Float, Float, Float),
     specular_reflection: (Float, Float, Float),
+    ///direction from the scene towards the eye, used for culling and the specular term
     camera: Point,
+    ///where the eye is, only used with a perspective projection as then the direction to the eye
+    ///is different for every triangle
+    eye: Point,
+    ///applied to everything as it is drawn, after any transformations applied with `apply`
+    view: Modtrix,
+    projection: Projection,
+    ///how close to and far from the eye things can be and still be drawn with perspective
+    near: Float,
+    far: Float,
+    shading: Shading,
 }
 
 impl<T: Color> Space<T> {
@@ -25,13 +65,21 @@ impl<T: Color> Space<T> {
     pub fn with_capacity(columns: usize) -> Self {
         Self {
             lin_space: Vec::with_capacity(columns),
-            tri_space: Vec::with_capacity(columns),
+            vertices: Vec::with_capacity(columns),
+            normals: Vec::with_capacity(columns),
+            tris: Vec::with_capacity(columns),
             lights: Vec::with_capacity(columns),
             ambient_light: T::default(),
             ambient_reflection: (0.0, 0.0, 0.0),
             diffuse_reflection: (0.0, 0.0, 0.0),
             specular_reflection: (0.0, 0.0, 0.0),
             camera: (0.0, 0.0, 1.0),
+            eye: (0.0, 0.0, 1.0),
+            view: Modtrix::IDENT,
+            projection: Projection::Orthographic,
+            near: 1.0,
+            far: Float::INFINITY,
+            shading: Shading::Flat,
         }
     }
 
@@ -49,16 +97,76 @@ impl<T: Color> Space<T> {
     ///adds a triangle to the Space
     ///note that p, q, and r, should be put in counter clockwise order. If you are looking at a
     ///clock which is like a triangle the p would be at 9:00, the q at 7:00, and the r at 4:00
+    ///every corner gets a vertex of its own, use `add_vertex` and `add_indexed_tri` when the
+    ///corners are shared with other triangles
     pub fn add_tri(&mut self, p: Point, q: Point, r: Point) {
-        self.tri_space.push([p.0, p.1, p.2, 1.0]);
-        self.tri_space.push([q.0, q.1, q.2, 1.0]);
-        self.tri_space.push([r.0, r.1, r.2, 1.0]);
+        let a = self.add_vertex(p, None);
+        let b = self.add_vertex(q, None);
+        let c = self.add_vertex(r, None);
+        self.add_indexed_tri(a, b, c);
+    }
+
+    ///adds a point which triangles can use as a corner and returns its index
+    ///the normal is that of the surface at the point, so smooth shading can use the real normals
+    ///of a curved surface instead of guessing from the triangles around it
+    pub fn add_vertex(&mut self, p: Point, nrm: Option<Point>) -> u32 {
+        let i = u32::try_from(self.vertices.len()).expect("too many vertices in the space");
+        let n = nrm.unwrap_or((0.0, 0.0, 0.0));
+        self.vertices.push([p.0, p.1, p.2, 1.0]);
+        self.normals.push([n.0, n.1, n.2, 0.0]);
+        i
+    }
+
+    ///adds a triangle between vertices returned by `add_vertex`, in the same order as `add_tri`
+    pub fn add_indexed_tri(&mut self, a: u32, b: u32, c: u32) {
+        debug_assert!([a, b, c]
+            .iter()
+            .all(|&i| (i as usize) < self.vertices.len()));
+        self.tris.push([a, b, c]);
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lin_space.len() / 2
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn tri_count(&self) -> usize {
+        self.tris.len()
+    }
+
+    ///the corners of every triangle in the space
+    pub fn triangles(&self) -> impl Iterator<Item = [Point; 3]> + '_ {
+        self.tris.iter().map(|t| t.map(|i| self.point(i)))
+    }
+
+    ///the corners of every triangle in the space, as indices which `vertex` takes
+    pub fn indexed_triangles(&self) -> &[[u32; 3]] {
+        &self.tris
+    }
+
+    ///where a vertex is and its normal, if it was given one
+    pub fn vertex(&self, i: u32) -> (Point, Option<Point>) {
+        let n = self.normals[i as usize];
+        let nrm = (n[0], n[1], n[2]);
+        (self.point(i), (nrm != (0.0, 0.0, 0.0)).then_some(nrm))
+    }
+
+    fn point(&self, i: u32) -> Point {
+        let v = self.vertices[i as usize];
+        (v[0], v[1], v[2])
     }
 
     pub fn add_light(&mut self, l: Light<T>) {
         self.lights.push(l);
     }
 
+    pub fn clear_lights(&mut self) {
+        self.lights.clear();
+    }
+
     pub fn set_ambient_light(&mut self, color: T) {
         self.ambient_light = color;
     }
@@ -79,17 +187,157 @@ impl<T: Color> Space<T> {
         self.camera = p;
     }
 
+    ///sets the matrix which takes the scene to the screen, culling and lighting happen before it
+    ///so they still see the scene the way it was built
+    pub fn set_view(&mut self, view: Modtrix) {
+        self.view = view;
+    }
+
+    pub fn set_eye(&mut self, p: Point) {
+        self.eye = p;
+    }
+
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+    }
+
+    pub fn set_shading(&mut self, shading: Shading) {
+        self.shading = shading;
+    }
+
+    ///a normal for every vertex
+    ///points added without a normal get the average of the normals of every triangle which
+    ///touches the point so curved surfaces made of flat triangles still look smooth, this goes
+    ///by position so triangles added with `add_tri` are smoothed across their separate corners
+    fn vertex_normals(&self) -> Vec<Point> {
+        let key = |v: &[Float; 4]| [v[0].to_bits(), v[1].to_bits(), v[2].to_bits()];
+        let given = |n: &[Float; 4]| n[..3] != [0.0; 3];
+        let mut sums: HashMap<[u64; 3], Point> = HashMap::new();
+        if !self.normals.iter().all(given) {
+            for (t, [p1, p2, p3]) in self.tris.iter().zip(self.triangles()) {
+                //not normalized so bigger triangles count for more
+                let n = gmath::norm(p1, p2, p3);
+                for &i in t {
+                    let sum = sums
+                        .entry(key(&self.vertices[i as usize]))
+                        .or_insert((0.0, 0.0, 0.0));
+                    *sum = gmath::add(*sum, n);
+                }
+            }
+        }
+        self.vertices
+            .iter()
+            .zip(&self.normals)
+            .map(|(v, n)| {
+                if given(n) {
+                    gmath::normalize((n[0], n[1], n[2]))
+                } else {
+                    //vertices no triangle uses are never drawn so their normal doesn't matter
+                    sums.get(&key(v))
+                        .map_or((0.0, 0.0, 0.0), |&n| gmath::normalize(n))
+                }
+            })
+            .collect()
+    }
+
+    ///sets the near and far planes, anything closer to the eye than `near` or further than `far`
+    ///is cut off, this only does something with a perspective projection as orthographic has no eye
+    pub fn set_depth_range(&mut self, near: Float, far: Float) {
+        self.near = near;
+        self.far = far;
+    }
+
+    ///the planes around what can be seen on a screen of the given size, in the space after the
+    ///view transform, `eye` is where the eye is in that space
+    fn view_volume(&self, eye: Point, width: Float, height: Float) -> Vec<Plane> {
+        match self.projection {
+            Projection::Orthographic => vec![
+                Plane::new((1.0, 0.0, 0.0), 0.0),
+                Plane::new((-1.0, 0.0, 0.0), width),
+                Plane::new((0.0, 1.0, 0.0), 0.0),
+                Plane::new((0.0, -1.0, 0.0), height),
+            ],
+            Projection::Perspective(f) => {
+                //the distance in front of the eye is eye.2 - z, so a point lands at
+                //eye.0 + (x - eye.0) * f / (eye.2 - z) on the screen, each edge of the screen is
+                //then a plane through the eye
+                let (ex, ey, ez) = eye;
+                let mut planes = vec![
+                    Plane::new((0.0, 0.0, -1.0), ez - self.near),
+                    Plane::new((f, 0.0, -ex), ex * (ez - f)),
+                    Plane::new((-f, 0.0, ex - width), (width - ex) * ez + f * ex),
+                    Plane::new((0.0, f, -ey), ey * (ez - f)),
+                    Plane::new((0.0, -f, ey - height), (height - ey) * ez + f * ey),
+                ];
+                if self.far.is_finite() {
+                    planes.push(Plane::new((0.0, 0.0, 1.0), self.far - ez));
+                }
+                planes
+            }
+        }
+    }
+
+    ///direction from `p` towards the eye
+    fn towards_eye(&self, p: Point) -> Point {
+        match self.projection {
+            Projection::Orthographic => gmath::normalize(self.camera),
+            Projection::Perspective(_) => gmath::normalize(gmath::sub(self.eye, p)),
+        }
+    }
+
+    ///where a point after the view transform lands on the screen, `eye` is where the eye is after
+    ///the view transform, the point must already be clipped to be in front of the eye
+    fn project(&self, v: Point, eye: Point) -> Point {
+        match self.projection {
+            Projection::Orthographic => v,
+            Projection::Perspective(focal) => {
+                //the homogeneous w, how many times further than the focal length the point is
+                let w = (eye.2 - v.2) / focal;
+                (eye.0 + (v.0 - eye.0) / w, eye.1 + (v.1 - eye.1) / w, v.2)
+            }
+        }
+    }
+
+    ///about how many pixels across a ball with the given center and radius would be when drawn,
+    ///with perspective this is for the part of the ball closest to the eye so it is never too small
+    pub fn screen_radius(&self, center: Point, radius: Float) -> Float {
+        let radius = radius * self.view.max_scale();
+        match self.projection {
+            Projection::Orthographic => radius,
+            Projection::Perspective(focal) => {
+                let eye = self.view.transform(self.eye);
+                let depth = eye.2 - self.view.transform(center).2 - radius;
+                radius * focal / depth.max(self.near)
+            }
+        }
+    }
+
+    ///where a point lands on the screen, points too close to the eye or behind it are treated as
+    ///being on the near plane so they still land somewhere
+    pub fn screen_point(&self, p: Point) -> Point {
+        let eye = self.view.transform(self.eye);
+        let v = self.view.transform(p);
+        match self.projection {
+            Projection::Orthographic => v,
+            Projection::Perspective(_) => self.project((v.0, v.1, v.2.min(eye.2 - self.near)), eye),
+        }
+    }
+
     pub fn clear_lines(&mut self) {
         self.lin_space.clear();
     }
 
     pub fn clear_tris(&mut self) {
-        self.tri_space.clear();
+        self.vertices.clear();
+        self.normals.clear();
+        self.tris.clear();
     }
 
     ///apply tranformation stored in a Modtrix
+    ///normals are transformed by its inverse transpose so they still face out from the surface
+    ///after a scale which is different along each axis
     pub fn apply(&mut self, transform: &Modtrix) {
-        let mult = |x: &Vec<[Float; 4]>| {
+        let mult = |x: &Vec<[Float; 4]>, transform: &Modtrix| {
             x.iter()
                 .map(|&v| {
                     [
@@ -113,30 +361,35 @@ impl<T: Color> Space<T> {
                 })
                 .collect()
         };
-        self.lin_space = mult(&self.lin_space);
-        self.tri_space = mult(&self.tri_space);
+        self.lin_space = mult(&self.lin_space, transform);
+        self.vertices = mult(&self.vertices, transform);
+        self.normals = mult(&self.normals, &transform.normal_matrix());
+    }
+}
+
+impl<T: Color> Default for Space<T> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-fn phong_color<T: Color>(p1: Point, p2: Point, p3: Point, s: Arc<&Space<T>>) -> T {
-    use gmath::{add, dot, norm, normalize, scale, sub};
+///the color of the point `p` on a surface facing `nrm`, which must be normalized
+fn lit_color<T: Color>(p: Point, nrm: Point, s: &Space<T>) -> T {
+    use gmath::{dot, normalize, scale, sub};
 
     const DISPERSION: Float = 2.0;
-    const THIRD: Float = 1.0 / 3.0;
 
     let mut color = s.ambient_light.mult(s.ambient_reflection);
-    let cm = scale(THIRD, add(add(p1, p2), p3));
 
     for l in &s.lights {
         //diffuse
-        let tol = normalize(sub(l.pos, cm));
-        let nrm = normalize(norm(p1, p2, p3));
+        let tol = normalize(sub(l.pos, p));
         let d = dot(nrm, tol);
         color += l.col.mult(scale(d, s.diffuse_reflection));
         //specular
         let r = sub(scale(2.0 * d, nrm), tol);
         let c = scale(
-            dot(r, normalize(s.camera)).max(0.0).powf(DISPERSION),
+            dot(r, s.towards_eye(p)).max(0.0).powf(DISPERSION),
             s.specular_reflection,
         );
         color += l.col.mult(c);
@@ -144,33 +397,121 @@ fn phong_color<T: Color>(p1: Point, p2: Point, p3: Point, s: Arc<&Space<T>>) ->
     color
 }
 
+fn phong_color<T: Color>(p1: Point, p2: Point, p3: Point, s: &Space<T>) -> T {
+    use gmath::{add, norm, normalize, scale};
+
+    const THIRD: Float = 1.0 / 3.0;
+
+    let cm = scale(THIRD, add(add(p1, p2), p3));
+    lit_color(cm, normalize(norm(p1, p2, p3)), s)
+}
+
+fn rgb<T: Color>(c: T) -> [Float; 3] {
+    [c.red() as Float, c.green() as Float, c.blue() as Float]
+}
+
 ///draws the lines currently in the space to a given screen
 //TODO: figure out if the arguments both have to have type U
 pub fn draw_space<U: Color>(space: &Space<U>, s: &mut Screen<U>) {
+    let eye = space.view.transform(space.eye);
+    let planes = space.view_volume(eye, s.width() as Float, s.height() as Float);
+    let v = &space.view;
     space.lin_space.windows(2).step_by(2).for_each(|w| {
-        let p1 = (w[0][0], w[0][1], w[0][2]);
-        let p2 = (w[1][0], w[1][1], w[1][2]);
-        s.draw_line(p1, p2, U::random_color());
+        let p1 = v.transform((w[0][0], w[0][1], w[0][2]));
+        let p2 = v.transform((w[1][0], w[1][1], w[1][2]));
+        if let Some((p1, p2)) = clip::clip_line(p1, p2, &planes) {
+            s.draw_line(
+                space.project(p1, eye),
+                space.project(p2, eye),
+                U::random_color(),
+            );
+        }
     });
-    let view = space.camera;
-    let aspace = Arc::new(space);
+
+    let normals = match space.shading {
+        Shading::Gouraud | Shading::Phong => space.vertex_normals(),
+        Shading::Wireframe | Shading::Flat => Vec::new(),
+    };
+    //each vertex is put through the view once no matter how many triangles share it
+    let viewed: Vec<Point> = space
+        .vertices
+        .iter()
+        .map(|w| v.transform((w[0], w[1], w[2])))
+        .collect();
     let t: Vec<_> = space
-        .tri_space
-        .windows(3)
-        .step_by(3)
-        .filter_map(|w| {
-            let p1 = (w[0][0], w[0][1], w[0][2]);
-            let p2 = (w[1][0], w[1][1], w[1][2]);
-            let p3 = (w[2][0], w[2][1], w[2][2]);
+        .tris
+        .iter()
+        .zip(space.triangles())
+        .filter_map(|(&i, [p1, p2, p3])| {
             let snorm = gmath::norm(p1, p2, p3);
-            if gmath::dot(snorm, view) > 0.0 {
-                Some((p1, p2, p3, phong_color(p1, p2, p3, aspace.clone())))
+            if space.shading == Shading::Wireframe || gmath::dot(snorm, space.towards_eye(p1)) > 0.0
+            {
+                Some((i, [p1, p2, p3]))
             } else {
                 None
             }
         })
         .collect();
 
-    t.iter()
-        .for_each(|&(p1, p2, p3, c)| s.draw_tri(p1, p2, p3, c));
+    //whatever is left after clipping is convex so it can be drawn as a fan
+    fn fan<V: Copy>(poly: &[V], mut draw: impl FnMut([V; 3])) {
+        for i in 1..poly.len().saturating_sub(1) {
+            draw([poly[0], poly[i], poly[i + 1]]);
+        }
+    }
+
+    //gouraud and phong light each pixel instead, so only these need the one color for the face
+    t.iter().for_each(|&(i, p)| match space.shading {
+        Shading::Wireframe => {
+            let c = phong_color(p[0], p[1], p[2], space);
+            let [q1, q2, q3] = i.map(|k| viewed[k as usize]);
+            for (a, b) in [(q1, q2), (q2, q3), (q3, q1)] {
+                if let Some((a, b)) = clip::clip_line(a, b, &planes) {
+                    s.draw_line(space.project(a, eye), space.project(b, eye), c);
+                }
+            }
+        }
+        Shading::Flat => {
+            let c = phong_color(p[0], p[1], p[2], space);
+            let poly = clip::clip_polygon(&i.map(|k| viewed[k as usize]), &planes);
+            let poly: Vec<_> = poly.into_iter().map(|p| space.project(p, eye)).collect();
+            fan(&poly, |[p1, p2, p3]| s.draw_tri(p1, p2, p3, c));
+        }
+        Shading::Gouraud => {
+            let corners: [(Point, [Float; 3]); 3] = std::array::from_fn(|k| {
+                let col = lit_color(p[k], normals[i[k] as usize], space);
+                (viewed[i[k] as usize], rgb(col))
+            });
+            let poly = clip::clip_polygon(&corners, &planes);
+            let poly: Vec<_> = poly
+                .into_iter()
+                .map(|(p, a)| (space.project(p, eye), a))
+                .collect();
+            fan(&poly, |corners| {
+                s.draw_tri_with(corners, |a| U::from_rgb((a[0], a[1], a[2])))
+            });
+        }
+        Shading::Phong => {
+            //the normal and the point before the view transform so lighting sees the scene as
+            //it was built
+            let corners: [(Point, [Float; 6]); 3] = std::array::from_fn(|k| {
+                let n = normals[i[k] as usize];
+                (
+                    viewed[i[k] as usize],
+                    [n.0, n.1, n.2, p[k].0, p[k].1, p[k].2],
+                )
+            });
+            let poly = clip::clip_polygon(&corners, &planes);
+            let poly: Vec<_> = poly
+                .into_iter()
+                .map(|(p, a)| (space.project(p, eye), a))
+                .collect();
+            fan(&poly, |corners| {
+                s.draw_tri_with(corners, |a| {
+                    let nrm = gmath::normalize((a[0], a[1], a[2]));
+                    lit_color((a[3], a[4], a[5]), nrm, space)
+                })
+            });
+        }
+    });
 }
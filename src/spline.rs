@@ -0,0 +1,107 @@
+//!knobs given by keyframes, a smooth curve through values at chosen frames
+
+use crate::{draw, easing, Float};
+
+///a curve through keys, each a frame and the knob's value there, before the first key the knob
+///has the first value and after the last key it has the last
+#[derive(Clone, PartialEq, Debug)]
+pub enum Spline {
+    ///goes through every `(frame, value)` key, the slope at each is the one from the key before
+    ///it to the key after it, or to the one next to it at the ends
+    CatmullRom(Vec<(Float, Float)>),
+    ///goes through every `(frame, value, slope)` key with the given slope, in value per frame
+    Hermite(Vec<(Float, Float, Float)>),
+    ///`(frame, value)` points in fours which share their ends, like `draw::add_bezier`, the curve
+    ///goes through every third point starting with the first and is pulled towards the two
+    ///between, these must be in the frames between the points on either side of them
+    Bezier(Vec<(Float, Float)>),
+}
+
+impl Spline {
+    ///the frames of the keys the curve goes through
+    pub fn keys(&self) -> Vec<Float> {
+        match self {
+            Spline::CatmullRom(keys) => keys.iter().map(|k| k.0).collect(),
+            Spline::Hermite(keys) => keys.iter().map(|k| k.0).collect(),
+            Spline::Bezier(points) => points.iter().step_by(3).map(|p| p.0).collect(),
+        }
+    }
+
+    ///what is wrong with the keys, if the curve can't be evaluated
+    pub fn problem(&self) -> Option<String> {
+        if let Spline::Bezier(points) = self {
+            if points.len() % 3 != 1 {
+                return Some(format!(
+                    "a bezier spline needs one more than a multiple of 3 points, found {}",
+                    points.len()
+                ));
+            }
+        }
+        let frames = self.keys();
+        if frames.len() < 2 {
+            return Some(format!(
+                "a spline needs at least 2 keys, found {}",
+                frames.len()
+            ));
+        }
+        if let Some(w) = frames.windows(2).find(|w| w[1] <= w[0]) {
+            return Some(format!(
+                "the keys must be in order of frame, {} comes after {}",
+                w[1], w[0]
+            ));
+        }
+        if let Spline::Bezier(points) = self {
+            for p in points.chunks(3).collect::<Vec<_>>().windows(2) {
+                let (start, end) = (p[0][0].0, p[1][0].0);
+                let outside = p[0][1..].iter().find(|c| c.0 < start || c.0 > end);
+                if let Some(c) = outside {
+                    return Some(format!(
+                        "the control point at frame {} is not between the keys at {} and {}",
+                        c.0, start, end
+                    ));
+                }
+            }
+        }
+        None
+    }
+
+    ///the value at a frame, the keys must have no `problem`
+    pub fn value(&self, frame: Float) -> Float {
+        let frames = self.keys();
+        let last = frames.len() - 1;
+        //the piece of the curve the frame is in, the ends are held outside the keys
+        let i = match frames.iter().rposition(|&k| k <= frame) {
+            None => 0,
+            Some(i) if i == last => last - 1,
+            Some(i) => i,
+        };
+        let (f0, f1) = (frames[i], frames[i + 1]);
+        let t = ((frame - f0) / (f1 - f0)).clamp(0.0, 1.0);
+        let dt = f1 - f0;
+        match self {
+            Spline::CatmullRom(keys) => {
+                let slope = |j: usize| {
+                    let (a, b) = (keys[j.saturating_sub(1)], keys[(j + 1).min(last)]);
+                    (b.1 - a.1) / (b.0 - a.0)
+                };
+                draw::hermite(
+                    keys[i].1,
+                    keys[i + 1].1,
+                    slope(i) * dt,
+                    slope(i + 1) * dt,
+                    t,
+                )
+            }
+            Spline::Hermite(keys) => {
+                let (k0, k1) = (keys[i], keys[i + 1]);
+                draw::hermite(k0.1, k1.1, k0.2 * dt, k1.2 * dt, t)
+            }
+            Spline::Bezier(points) => {
+                let p = &points[3 * i..=3 * i + 3];
+                let across = |c: (Float, Float)| (c.0 - f0) / dt;
+                let s = easing::along(across(p[1]), across(p[2]), t);
+                draw::bezier(p[0].1, p[1].1, p[2].1, p[3].1, s)
+            }
+        }
+    }
+}
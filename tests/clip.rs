@@ -0,0 +1,119 @@
+use graphics::space::clip::{clip_line, clip_polygon, Plane};
+use graphics::{Engine, Light, RGB8Color};
+
+///everything with x at most 10
+fn left_of_ten() -> Vec<Plane> {
+    vec![Plane::new((-1.0, 0.0, 0.0), 10.0)]
+}
+
+#[test]
+fn polygons_inside_are_unchanged() {
+    let tri = [(0.0, 0.0, 0.0), (5.0, 0.0, 0.0), (0.0, 5.0, 0.0)];
+    assert_eq!(clip_polygon(&tri, &left_of_ten()), tri);
+}
+
+#[test]
+fn polygons_outside_are_removed() {
+    let tri = [(11.0, 0.0, 0.0), (15.0, 0.0, 0.0), (11.0, 5.0, 0.0)];
+    assert!(clip_polygon(&tri, &left_of_ten()).is_empty());
+}
+
+#[test]
+fn triangle_with_one_corner_outside_becomes_a_quad() {
+    let tri = [(0.0, 0.0, 0.0), (20.0, 0.0, 0.0), (0.0, 20.0, 0.0)];
+    let quad = clip_polygon(&tri, &left_of_ten());
+    assert_eq!(
+        quad,
+        [
+            (0.0, 0.0, 0.0),
+            (10.0, 0.0, 0.0),
+            (10.0, 10.0, 0.0),
+            (0.0, 20.0, 0.0)
+        ]
+    );
+}
+
+#[test]
+fn triangle_with_two_corners_outside_stays_a_triangle() {
+    //everything with x at least 10
+    let planes = vec![Plane::new((1.0, 0.0, 0.0), -10.0)];
+    let tri = [(0.0, 0.0, 4.0), (20.0, 0.0, 4.0), (0.0, 20.0, 4.0)];
+    assert_eq!(
+        clip_polygon(&tri, &planes),
+        [(10.0, 0.0, 4.0), (20.0, 0.0, 4.0), (10.0, 10.0, 4.0)]
+    );
+}
+
+#[test]
+fn lines_are_cut_where_they_leave() {
+    let planes = left_of_ten();
+    assert_eq!(
+        clip_line((0.0, 0.0, 0.0), (20.0, 10.0, -20.0), &planes),
+        Some(((0.0, 0.0, 0.0), (10.0, 5.0, -10.0)))
+    );
+    assert_eq!(
+        clip_line((20.0, 10.0, -20.0), (0.0, 0.0, 0.0), &planes),
+        Some(((10.0, 5.0, -10.0), (0.0, 0.0, 0.0)))
+    );
+    assert_eq!(
+        clip_line((11.0, 0.0, 0.0), (20.0, 10.0, -20.0), &planes),
+        None
+    );
+}
+
+#[test]
+fn lines_outside_different_planes_are_removed() {
+    let planes = vec![
+        Plane::new((1.0, 0.0, 0.0), 0.0),
+        Plane::new((0.0, 1.0, 0.0), 0.0),
+    ];
+    //each end is inside one plane but the line never gets inside both
+    assert_eq!(clip_line((-5.0, 1.0, 0.0), (1.0, -5.0, 0.0), &planes), None);
+}
+
+///counts the lit pixels in the top and bottom halves of the image
+fn halves(eng: &Engine<RGB8Color>) -> (usize, usize) {
+    let bytes = eng.ppm_byte_vec();
+    let pixels = &bytes[bytes.len() - 500 * 500 * 3..];
+    let lit = |rows: &[u8]| rows.chunks(3).filter(|c| c != &[0, 0, 0]).count();
+    let (top, bottom) = pixels.split_at(250 * 500 * 3);
+    (lit(top), lit(bottom))
+}
+
+#[test]
+fn geometry_behind_the_eye_is_not_drawn() {
+    let mut eng = Engine::<RGB8Color>::with_screen_dims(500, 500);
+    eng.add_light(Light::new((0.0, 1000.0, 0.0), (255, 255, 255).into()));
+    eng.set_constants([0.1, 0.1, 0.1, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5]);
+    eng.look_at((0.0, 0.0, 0.0), (0.0, 0.0, -1.0));
+    eng.set_focal(250.0);
+    //a floor under the eye going from in front of it to behind it, if the part behind were
+    //projected it would land upside down in the top half
+    eng.add_box((-100.0, -50.0, 300.0), 200.0, 20.0, 600.0);
+    eng.add_line((0.0, -60.0, 300.0), (0.0, -60.0, -300.0));
+    eng.apply_sys();
+    eng.draw_space();
+    let (top, bottom) = halves(&eng);
+    assert_eq!(top, 0);
+    assert!(bottom > 0);
+}
+
+#[test]
+fn far_plane_cuts_off_distant_geometry() {
+    let draw = |far: f64| {
+        let mut eng = Engine::<RGB8Color>::with_screen_dims(500, 500);
+        eng.add_light(Light::new((0.0, 1000.0, 0.0), (255, 255, 255).into()));
+        eng.set_constants([0.1, 0.1, 0.1, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5]);
+        eng.look_at((0.0, 0.0, 0.0), (0.0, 0.0, -1.0));
+        eng.set_focal(250.0);
+        eng.set_depth_range(1.0, far);
+        eng.add_box((-100.0, -50.0, -10.0), 200.0, 20.0, 1000.0);
+        eng.apply_sys();
+        eng.draw_space();
+        halves(&eng).1
+    };
+    let all = draw(f64::INFINITY);
+    let some = draw(200.0);
+    assert!(some > 0);
+    assert!(some < all);
+}
@@ -0,0 +1,24 @@
+//!helpers shared by the integration tests, not every test uses all of them
+#![allow(dead_code)]
+
+use graphics::{Diagnostics, Engine, RGB8Color, Script};
+
+///everything reported about a script, as it is shown to whoever wrote it
+pub fn problems(src: &str) -> String {
+    let mut diag = Diagnostics::with_source("test.mdl", src);
+    Script::from_mdl(src, &mut diag);
+    diag.to_string()
+}
+
+///runs a script with no problems on a 100 by 100 screen and gives back the picture as a ppm
+pub fn draw(src: &str) -> Vec<u8> {
+    //round shapes are drawn coarsely to keep the tests quick
+    let src = &format!("resolution 20\n{}", src);
+    let mut diag = Diagnostics::with_source("test.mdl", src);
+    let script = Script::from_mdl(src, &mut diag).expect("failed to parse script");
+    assert!(diag.is_empty(), "{}", diag);
+    let mut eng = Engine::<RGB8Color>::with_screen_dims(100, 100);
+    script.exec(&mut eng, &mut diag);
+    assert!(diag.is_empty(), "{}", diag);
+    eng.ppm_byte_vec()
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use common::draw;
+use graphics::{Diagnostics, Script};
+
+#[test]
+fn shapes_can_be_drawn_in_a_saved_coordinate_system() {
+    let saved = draw(
+        "push\nmove 50 50 0\nrotate z 30\nsave_coord_system tilted\npop\n\
+         box -10 10 10 20 20 20 tilted\n\
+         sphere 20 0 0 5 tilted\n\
+         torus -20 0 0 2 5 tilted\n\
+         mesh :data/cube.obj tilted",
+    );
+    let stacked = draw(
+        "push\nmove 50 50 0\nrotate z 30\n\
+         box -10 10 10 20 20 20\n\
+         sphere 20 0 0 5\n\
+         torus -20 0 0 2 5\n\
+         mesh :data/cube.obj\npop",
+    );
+    assert!(saved.iter().any(|&b| b != 0));
+    assert!(saved == stacked);
+}
+
+#[test]
+fn the_stack_is_left_as_it_was() {
+    //the box is drawn in `up` but the sphere after it is still drawn in the moved stack
+    let saved = draw(
+        "push\nmove 0 30 0\nsave_coord_system up\npop\nmove 50 50 0\n\
+         box -5 5 5 10 10 10 up\nsphere 0 0 0 10",
+    );
+    let stacked =
+        draw("push\nmove 0 30 0\nbox -5 5 5 10 10 10\npop\nmove 50 50 0\nsphere 0 0 0 10");
+    assert!(saved == stacked);
+}
+
+#[test]
+fn saving_again_replaces_the_old_one() {
+    let saved = draw(
+        "save_coord_system here\nmove 50 50 0\nsave_coord_system here\nmove -50 -50 0\n\
+         sphere 0 0 0 10 here",
+    );
+    assert!(saved == draw("move 50 50 0\nsphere 0 0 0 10"));
+}
+
+#[test]
+fn coordinate_systems_must_be_saved_before_they_are_used() {
+    let src = "box 0 0 0 1 1 1 later\nsave_coord_system later\nsphere 0 0 0 1 later";
+    let mut diag = Diagnostics::with_source("test.mdl", src);
+    assert!(Script::from_mdl(src, &mut diag).is_none());
+    let text = diag.to_string();
+    assert!(text.contains("E0018"), "{}", text);
+    assert_eq!(text.matches("E0018").count(), 1, "{}", text);
+}
+
+///which pixels are drawn, lines are given random colors so only where they are can be compared
+fn lit(image: Vec<u8>) -> Vec<bool> {
+    image[image.len() - 100 * 100 * 3..]
+        .chunks(3)
+        .map(|c| c != [0, 0, 0])
+        .collect()
+}
+
+#[test]
+fn each_end_of_a_line_can_be_in_its_own_coordinate_system() {
+    let saved = lit(draw(
+        "push\nmove 10 20 0\nsave_coord_system a\npop\n\
+         push\nmove 80 70 0\nrotate z 90\nsave_coord_system b\npop\n\
+         move 5 5 0\n\
+         line 0 0 0 a 0 10 0 b\n\
+         line 0 0 0 a 10 0 0",
+    ));
+    assert!(saved == lit(draw("line 10 20 0 70 70 0\nline 10 20 0 15 5 0")));
+    assert!(saved != lit(draw("line 10 20 0 80 80 0\nline 10 20 0 15 5 0")));
+
+    let src = "save_coord_system a\nline 0 0 0 a 1 1 1 b";
+    let mut diag = Diagnostics::with_source("test.mdl", src);
+    assert!(Script::from_mdl(src, &mut diag).is_none());
+    assert!(diag.to_string().contains("`b`"), "{}", diag);
+}
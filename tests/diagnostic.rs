@@ -0,0 +1,177 @@
+use graphics::{Code, Diagnostic, Diagnostics, Engine, RGB8Color, Script, Severity, Span};
+
+fn parse(src: &str) -> (Option<Script>, Diagnostics) {
+    let mut diag = Diagnostics::with_source("bad.mdl", src);
+    let script = Script::from_mdl(src, &mut diag);
+    (script, diag)
+}
+
+const MISTAKES: &str = "push\nmove 1 2\n\tsphere 0 0 0 x\npop\npop\nrotate w 30\n";
+
+#[test]
+fn every_mistake_is_reported_in_one_run() {
+    let (script, diag) = parse(MISTAKES);
+    assert!(script.is_none());
+    let found: Vec<_> = diag.items().iter().map(|d| (d.code, d.span)).collect();
+    assert_eq!(
+        found,
+        [
+            (Code::ExpectedNumber, Some(Span::new(2, 9, 1))),
+            (Code::ExpectedNumber, Some(Span::new(3, 15, 1))),
+            (Code::InvalidAxis, Some(Span::new(6, 8, 1))),
+            (Code::UnbalancedPop, Some(Span::new(5, 1, 3))),
+        ]
+    );
+    assert_eq!(diag.error_count(), 4);
+}
+
+#[test]
+fn problems_are_shown_in_source_order_with_snippets() {
+    let (_, diag) = parse(MISTAKES);
+    //the caret under the tabbed line keeps the tab so it lines up with the text
+    let expected = "\
+error[E0004]: expected a number for move
+ --> bad.mdl:2:9
+  |
+2 | move 1 2
+  |         ^
+
+error[E0004]: expected a number for sphere
+ --> bad.mdl:3:15
+  |
+3 | \tsphere 0 0 0 x
+  | \t             ^
+
+error[E0013]: `pop` without a matching `push`, there is nothing to pop
+ --> bad.mdl:5:1
+  |
+5 | pop
+  | ^^^
+
+error[E0006]: `w` is not an axis, expected x, y, or z
+ --> bad.mdl:6:8
+  |
+6 | rotate w 30
+  |        ^
+
+could not run script due to 4 errors
+";
+    assert_eq!(diag.to_string(), expected);
+}
+
+#[test]
+fn warnings_alone_still_run() {
+    let (script, diag) = parse("box 0 0 0 1 1 1\nvary k 0 9 0 1\nmove 1 1 1 k\nframes 10\n");
+    assert!(script.is_some());
+    assert!(!diag.has_errors());
+    assert_eq!(diag.items()[0].severity(), Severity::Warning);
+    let text = diag.to_string();
+    assert!(
+        text.starts_with("warning[W0002]: no `basename` given"),
+        "{}",
+        text
+    );
+    assert!(text.contains(" --> bad.mdl:4:1\n"), "{}", text);
+    assert!(!text.contains("could not run"), "{}", text);
+
+    let (_, diag) = parse("frames 3\nmove 1 1 1 $\n");
+    let text = diag.to_string();
+    assert!(
+        text.ends_with("could not run script due to 1 error\n"),
+        "{}",
+        text
+    );
+}
+
+#[test]
+fn running_keeps_the_warnings_from_parsing_once() {
+    let (script, mut diag) = parse("basename b\nbox 0 0 0 1 1 1\n");
+    let mut eng = Engine::<RGB8Color>::with_screen_dims(10, 10);
+    script.unwrap().exec(&mut eng, &mut diag);
+    let codes: Vec<_> = diag.items().iter().map(|d| d.code).collect();
+    assert_eq!(codes, [Code::UnusedBasename]);
+    assert!(diag.to_string().contains(" --> bad.mdl:1:1\n"), "{}", diag);
+}
+
+#[test]
+fn a_mesh_that_cannot_be_loaded_is_shown_in_the_source() {
+    let (script, mut diag) = parse("box 0 0 0 1 1 1\nmesh :data/missing.obj\n");
+    let mut eng = Engine::<RGB8Color>::with_screen_dims(10, 10);
+    script.unwrap().exec(&mut eng, &mut diag);
+    let text = diag.to_string();
+    //the reason comes from the os, so only the start of the message is checked
+    let (message, rest) = text.split_once('\n').unwrap();
+    assert!(
+        message.starts_with("error[E0017]: cannot load mesh `data/missing.obj`: "),
+        "{}",
+        text
+    );
+    //a line continuation would eat the space before the arrow
+    let expected = " --> bad.mdl:2:1
+  |
+2 | mesh :data/missing.obj
+  | ^^^^^^^^^^^^^^^^^^^^^^
+
+could not run script due to 1 error
+";
+    assert_eq!(rest, expected);
+}
+
+#[test]
+fn problems_without_a_place_name_the_file() {
+    let mut diag = Diagnostics::with_source("a.mdl_intermediate_language", "");
+    diag.report(
+        Code::UnknownKnob,
+        None,
+        "knob `k` is never given a value".into(),
+    );
+    assert_eq!(
+        diag.to_string(),
+        "error[E0012]: knob `k` is never given a value\n --> a.mdl_intermediate_language\n\n\
+         could not run script due to 1 error\n"
+    );
+    let d = Diagnostic::new(Code::InvalidAxis, None, "no".into());
+    assert_eq!(d.to_string(), "error[E0006]: no");
+}
+
+#[test]
+fn codes_never_change() {
+    let codes = [
+        (Code::UnexpectedCharacter, "E0001"),
+        (Code::InvalidNumber, "E0002"),
+        (Code::ExpectedCommand, "E0003"),
+        (Code::ExpectedNumber, "E0004"),
+        (Code::ExpectedName, "E0005"),
+        (Code::InvalidAxis, "E0006"),
+        (Code::UndefinedConstants, "E0007"),
+        (Code::InvalidShading, "E0008"),
+        (Code::ExpectedColon, "E0009"),
+        (Code::InvalidFrame, "E0010"),
+        (Code::VaryWithoutFrames, "E0011"),
+        (Code::UnknownKnob, "E0012"),
+        (Code::UnbalancedPop, "E0013"),
+        (Code::CameraLooksAtItself, "E0014"),
+        (Code::InvalidFocal, "E0015"),
+        (Code::InvalidResolution, "E0016"),
+        (Code::MeshLoadFailed, "E0017"),
+        (Code::UnknownCoordSystem, "E0018"),
+        (Code::UnknownKnobList, "E0019"),
+        (Code::InvalidEasing, "E0020"),
+        (Code::InvalidSpline, "E0021"),
+        (Code::InvalidFrameRange, "E0022"),
+        (Code::AnimationSaveFailed, "E0023"),
+        (Code::Unsupported, "W0001"),
+        (Code::MissingBasename, "W0002"),
+        (Code::UnusedBasename, "W0003"),
+        (Code::OverlappingVary, "W0004"),
+    ];
+    for (code, s) in codes {
+        assert_eq!(code.as_str(), s);
+        let severity = if s.starts_with('E') {
+            Severity::Error
+        } else {
+            Severity::Warning
+        };
+        assert_eq!(code.severity(), severity);
+    }
+}
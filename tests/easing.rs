@@ -0,0 +1,98 @@
+mod common;
+
+use common::problems;
+use graphics::{Diagnostics, Easing, Engine, RGB8Color, Script};
+
+const ALL: [Easing; 9] = [
+    Easing::Linear,
+    Easing::EaseIn,
+    Easing::EaseOut,
+    Easing::EaseInOut,
+    Easing::Cubic,
+    Easing::Sine,
+    Easing::Bounce,
+    Easing::Step(4),
+    Easing::CubicBezier(0.3, -0.5, 0.7, 1.5),
+];
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-9
+}
+
+#[test]
+fn every_easing_starts_at_0_and_ends_at_1() {
+    for e in ALL {
+        assert!(close(e.apply(0.0), 0.0), "{:?}", e);
+        assert!(close(e.apply(1.0), 1.0), "{:?}", e);
+    }
+}
+
+#[test]
+fn easings_have_their_shapes() {
+    assert!(Easing::EaseIn.apply(0.25) < 0.25);
+    assert!(Easing::EaseOut.apply(0.25) > 0.25);
+    for e in [Easing::EaseInOut, Easing::Cubic, Easing::Sine] {
+        assert!(e.apply(0.2) < 0.2, "{:?}", e);
+        assert!(close(e.apply(0.5), 0.5), "{:?}", e);
+        assert!(e.apply(0.8) > 0.8, "{:?}", e);
+    }
+    assert!(Easing::Cubic.apply(0.2) < Easing::Sine.apply(0.2));
+    //the bounce reaches the end early then drops away from it
+    assert!(close(Easing::Bounce.apply(1.0 / 2.75), 1.0));
+    assert!(Easing::Bounce.apply(0.5) < 1.0);
+    assert_eq!(Easing::Step(4).apply(0.2), 0.0);
+    assert_eq!(Easing::Step(4).apply(0.3), 0.25);
+    assert_eq!(Easing::Step(4).apply(0.99), 0.75);
+    //css `ease` is a cubic bezier
+    let ease = Easing::CubicBezier(0.25, 0.1, 0.25, 1.0);
+    assert!((ease.apply(0.5) - 0.8024).abs() < 1e-3);
+    let overshoot = Easing::CubicBezier(0.3, -0.5, 0.7, 1.5);
+    assert!(overshoot.apply(0.1) < 0.0);
+    assert!(overshoot.apply(0.9) > 1.0);
+}
+
+#[test]
+fn vary_spaces_values_by_its_easing() {
+    let mut eng = Engine::<RGB8Color>::with_screen_dims(10, 10);
+    eng.add_vary("k".into(), 0, 8, 10.0, 20.0, Easing::Step(2), 9)
+        .unwrap();
+    let values: Vec<_> = (0..=8).map(|f| eng.get_knob_val("k".into(), f)).collect();
+    assert_eq!(
+        values,
+        [10.0, 10.0, 10.0, 10.0, 15.0, 15.0, 15.0, 15.0, 20.0]
+    );
+    eng.add_vary("e".into(), 0, 8, 10.0, 20.0, Easing::EaseIn, 9)
+        .unwrap();
+    assert!(eng.get_knob_val("e".into(), 2) < 12.5);
+}
+
+#[test]
+fn easings_are_read_from_scripts() {
+    let src = "frames 10\nbasename e\nvary a 0 9 0 1\nvary b 0 9 0 1 ease-in-out\n\
+               vary c 0 9 0 1 step\nvary d 0 9 0 1 step 3\n\
+               vary e 0 9 0 1 cubic-bezier 0.1 0.2 0.3 0.4";
+    let mut diag = Diagnostics::with_source("test.mdl", src);
+    let script = Script::from_mdl(src, &mut diag).expect("failed to parse script");
+    let text = script.disassemble();
+    assert!(text.contains("vary a 0 9 0 1\n"), "{}", text);
+    assert!(text.contains("vary b 0 9 0 1 ease-in-out\n"), "{}", text);
+    assert!(text.contains("vary c 0 9 0 1 step 1\n"), "{}", text);
+    assert!(text.contains("vary d 0 9 0 1 step 3\n"), "{}", text);
+    assert!(
+        text.contains("vary e 0 9 0 1 cubic-bezier 0.1 0.2 0.3 0.4\n"),
+        "{}",
+        text
+    );
+}
+
+#[test]
+fn bad_easings_are_reported() {
+    let text = problems("frames 10\nbasename e\nvary a 0 9 0 1 wobble");
+    assert!(text.contains("E0020"), "{}", text);
+    assert!(text.contains("`wobble`"), "{}", text);
+    let text = problems("frames 10\nbasename e\nvary a 0 9 0 1 step 0");
+    assert!(text.contains("E0020"), "{}", text);
+    let text = problems("frames 10\nbasename e\nvary a 0 9 0 1 cubic-bezier 1.5 0 0.5 1");
+    assert!(text.contains("E0020"), "{}", text);
+    assert!(text.contains("1.5"), "{}", text);
+}
@@ -0,0 +1,277 @@
+use std::collections::HashSet;
+use std::fs;
+
+use graphics::gif::Frame;
+use graphics::{Diagnostics, Engine, Gif, GifOptions, RGB8Color, Script};
+
+///what a gif holds, read back by the small decoder below
+#[derive(Debug, Default)]
+struct Decoded {
+    width: usize,
+    height: usize,
+    loops: Option<u16>,
+    delays: Vec<u16>,
+    ///3 bytes a pixel with the top row first
+    frames: Vec<Vec<u8>>,
+}
+
+fn decode(bytes: &[u8]) -> Decoded {
+    assert_eq!(&bytes[..6], b"GIF89a");
+    let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]);
+    let mut gif = Decoded {
+        width: u16_at(6) as usize,
+        height: u16_at(8) as usize,
+        ..Decoded::default()
+    };
+    assert_eq!(bytes[10] & 0x80, 0, "there should be no global palette");
+    let blocks = |i: &mut usize| {
+        let mut out = Vec::new();
+        while bytes[*i] != 0 {
+            let n = bytes[*i] as usize;
+            out.push(bytes[*i + 1..*i + 1 + n].to_vec());
+            *i += n + 1;
+        }
+        *i += 1;
+        out
+    };
+    let mut i = 13;
+    loop {
+        match bytes[i] {
+            0x21 => {
+                let label = bytes[i + 1];
+                i += 2;
+                let data = blocks(&mut i);
+                if label == 0xff && data[0] == b"NETSCAPE2.0" {
+                    gif.loops = Some(u16::from_le_bytes([data[1][1], data[1][2]]));
+                } else if label == 0xf9 {
+                    gif.delays
+                        .push(u16::from_le_bytes([data[0][1], data[0][2]]));
+                }
+            }
+            0x2c => {
+                assert_eq!(
+                    (u16_at(i + 5) as usize, u16_at(i + 7) as usize),
+                    (gif.width, gif.height)
+                );
+                let packed = bytes[i + 9];
+                assert_eq!(packed & 0x80, 0x80, "every frame should have a palette");
+                let colors = 2 << (packed & 7);
+                let palette = &bytes[i + 10..i + 10 + 3 * colors];
+                i += 10 + 3 * colors;
+                let min_code_size = bytes[i];
+                i += 1;
+                let data = blocks(&mut i).concat();
+                let indices = lzw_decode(&data, min_code_size);
+                assert_eq!(indices.len(), gif.width * gif.height);
+                let rgb = indices
+                    .iter()
+                    .flat_map(|&k| palette[3 * k as usize..3 * k as usize + 3].to_vec())
+                    .collect();
+                gif.frames.push(rgb);
+            }
+            0x3b => break,
+            b => panic!("unexpected block {:#x} at {}", b, i),
+        }
+    }
+    gif
+}
+
+fn lzw_decode(data: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear = 1usize << min_code_size;
+    let fresh = || {
+        let mut table: Vec<Vec<u8>> = (0..clear).map(|k| vec![k as u8]).collect();
+        table.extend([vec![], vec![]]);
+        table
+    };
+    let mut table = fresh();
+    let mut size = min_code_size as usize + 1;
+    let mut prev: Option<Vec<u8>> = None;
+    let mut bit = 0;
+    let mut out = Vec::new();
+    loop {
+        let code = (0..size)
+            .filter(|b| data[(bit + b) / 8] >> ((bit + b) % 8) & 1 == 1)
+            .fold(0, |code, b| code | 1 << b);
+        bit += size;
+        if code == clear {
+            table = fresh();
+            size = min_code_size as usize + 1;
+            prev = None;
+            continue;
+        }
+        if code == clear + 1 {
+            break;
+        }
+        let entry = match (table.get(code), &prev) {
+            (Some(e), _) => e.clone(),
+            (None, Some(p)) => [p.clone(), vec![p[0]]].concat(),
+            (None, None) => panic!("code {} before the table has anything", code),
+        };
+        if let Some(p) = prev {
+            if table.len() < 4096 {
+                table.push([p, vec![entry[0]]].concat());
+            }
+        }
+        out.extend_from_slice(&entry);
+        prev = Some(entry);
+        if table.len() == 1 << size && size < 12 {
+            size += 1;
+        }
+    }
+    out
+}
+
+fn write(gif: &Gif) -> Decoded {
+    let mut bytes = Vec::new();
+    gif.write(&mut bytes).unwrap();
+    decode(&bytes)
+}
+
+///a number from 0 to 255 for each pixel that doesn't repeat in any way LZW can use
+fn noise(len: usize) -> impl Iterator<Item = u8> {
+    let mut state = 12345u32;
+    (0..len).map(move |_| {
+        state = state.wrapping_mul(1103515245).wrapping_add(12345);
+        (state >> 16) as u8
+    })
+}
+
+#[test]
+fn few_colors_are_kept_exactly() {
+    let (w, h) = (37, 23);
+    let colors = [[0, 0, 0], [255, 0, 0], [0, 128, 255], [9, 9, 9], [1, 2, 3]];
+    let rgb: Vec<u8> = (0..w * h)
+        .flat_map(|i| colors[(i / 7 + i % 5) % colors.len()])
+        .collect();
+    let options = GifOptions {
+        delay: 7,
+        loops: 3,
+        dither: false,
+    };
+    let mut gif = Gif::new(w, h, options);
+    gif.push(Frame::encode(w, h, &rgb, false));
+    //dithering does nothing when no colors are lost
+    gif.push(Frame::encode(w, h, &rgb, true));
+    let decoded = write(&gif);
+    assert_eq!((decoded.width, decoded.height), (w, h));
+    assert_eq!(decoded.delays, [7, 7]);
+    //the extension counts the times played after the first
+    assert_eq!(decoded.loops, Some(2));
+    assert_eq!(decoded.frames, [rgb.clone(), rgb]);
+}
+
+#[test]
+fn long_frames_clear_the_code_table() {
+    let (w, h) = (300, 200);
+    let palette: Vec<[u8; 3]> = (0..=255).map(|k| [k, 255 - k, k / 2]).collect();
+    let noisy: Vec<u8> = noise(w * h).flat_map(|k| palette[k as usize]).collect();
+    let two: Vec<u8> = noise(w * h)
+        .flat_map(|k| palette[(k & 1) as usize])
+        .collect();
+    let one = vec![40; w * h * 3];
+    let mut gif = Gif::new(w, h, GifOptions::default());
+    for frame in [&noisy, &two, &one] {
+        gif.push(Frame::encode(w, h, frame, false));
+    }
+    let decoded = write(&gif);
+    assert_eq!(decoded.frames, [noisy, two, one]);
+}
+
+#[test]
+fn many_colors_get_a_palette_of_256() {
+    let (w, h) = (256, 64);
+    let rgb: Vec<u8> = (0..w * h)
+        .flat_map(|i| {
+            let (x, y) = (i % w, i / w);
+            [x as u8, (y * 4) as u8, ((x + y) % 256) as u8]
+        })
+        .collect();
+    let mut gif = Gif::new(w, h, GifOptions::default());
+    gif.push(Frame::encode(w, h, &rgb, false));
+    gif.push(Frame::encode(w, h, &rgb, true));
+    let decoded = write(&gif);
+    for frame in &decoded.frames {
+        let colors: HashSet<_> = frame.chunks(3).collect();
+        assert!(colors.len() <= 256, "{} colors", colors.len());
+        let error: u64 = frame
+            .iter()
+            .zip(&rgb)
+            .map(|(&a, &b)| (a as i64 - b as i64).unsigned_abs())
+            .sum();
+        assert!(error / (rgb.len() as u64) < 8, "{}", error);
+    }
+    assert_ne!(decoded.frames[0], decoded.frames[1]);
+    //dithering keeps the overall color where it was
+    let mean = |f: &[u8], ch: usize| {
+        f.iter().skip(ch).step_by(3).map(|&v| v as f64).sum::<f64>() / (w * h) as f64
+    };
+    for ch in 0..3 {
+        assert!((mean(&decoded.frames[1], ch) - mean(&rgb, ch)).abs() < 1.0);
+    }
+}
+
+#[test]
+fn playing_once_needs_no_loop_extension() {
+    let frame = || Frame::encode(2, 2, &[7; 12], false);
+    let mut once = Gif::new(
+        2,
+        2,
+        GifOptions {
+            loops: 1,
+            ..GifOptions::default()
+        },
+    );
+    once.push(frame());
+    assert_eq!(write(&once).loops, None);
+    let mut forever = Gif::new(2, 2, GifOptions::default());
+    forever.push(frame());
+    let decoded = write(&forever);
+    assert_eq!(decoded.loops, Some(0));
+    assert_eq!(decoded.delays, [2]);
+    let too_wide = Gif::new(70000, 1, GifOptions::default());
+    assert!(too_wide.write(&mut Vec::new()).is_err());
+}
+
+///everything reported while making a 4 frame animation
+fn animate(basename: &str) -> Diagnostics {
+    let src = format!(
+        "frames 4\nbasename {}\nambient 200 200 200\nvary k 0 3 0 1\nmove 60 0 0 k\nbox 10 60 0 20 20 20",
+        basename
+    );
+    let mut diag = Diagnostics::with_source("test.mdl", &src);
+    let script = Script::from_mdl(&src, &mut diag).expect("failed to parse script");
+    assert!(diag.is_empty(), "{}", diag);
+    let mut eng = Engine::<RGB8Color>::with_screen_dims(100, 80);
+    eng.set_gif_options(GifOptions {
+        delay: 5,
+        ..GifOptions::default()
+    });
+    script.exec(&mut eng, &mut diag);
+    diag
+}
+
+#[test]
+fn animations_are_saved_as_gifs() {
+    let base = std::env::temp_dir().join("graphics_gif_test_animation");
+    let base = base.to_str().unwrap();
+    let diag = animate(base);
+    assert!(diag.is_empty(), "{}", diag);
+    let file = format!("{}.gif", base);
+    let decoded = decode(&fs::read(&file).unwrap());
+    fs::remove_file(&file).unwrap();
+    assert_eq!((decoded.width, decoded.height), (100, 80));
+    assert_eq!(decoded.delays, [5; 4]);
+    assert_eq!(decoded.frames.len(), 4);
+    assert!(decoded.frames[0].iter().any(|&b| b != 0));
+    //the box moves every frame
+    assert!(decoded.frames.windows(2).all(|f| f[0] != f[1]));
+}
+
+#[test]
+fn unsaveable_animations_are_reported() {
+    let text = animate("/graphics_no_such_directory/animation").to_string();
+    assert!(text.contains("E0023"), "{}", text);
+    assert!(text.contains("no_such_directory"), "{}", text);
+    //shown at the basename which couldn't be saved to
+    assert!(text.contains(" --> test.mdl:2:1\n"), "{}", text);
+}
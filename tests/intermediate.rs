@@ -0,0 +1,274 @@
+use graphics::{Diagnostics, Script, FORMAT_MAGIC, FORMAT_VERSION};
+
+///builds intermediate files by hand so the tests don't depend on the MDL parser
+#[derive(Default)]
+struct Bytes(Vec<u8>);
+
+impl Bytes {
+    fn op(mut self, code: u8) -> Self {
+        self.0.push(code);
+        self
+    }
+
+    fn f64s(mut self, vals: &[f64]) -> Self {
+        for v in vals {
+            self.0.extend_from_slice(&v.to_ne_bytes());
+        }
+        self
+    }
+
+    fn u16(mut self, v: u16) -> Self {
+        self.0.extend_from_slice(&v.to_ne_bytes());
+        self
+    }
+
+    fn u32(mut self, v: u32) -> Self {
+        self.0.extend_from_slice(&v.to_ne_bytes());
+        self
+    }
+
+    fn string(mut self, s: &str) -> Self {
+        self.0.extend_from_slice(s.as_bytes());
+        self.0.push(0);
+        self
+    }
+
+    ///puts the header in front and the checksum after, like a real intermediate file
+    fn framed(self) -> Vec<u8> {
+        let mut out = FORMAT_MAGIC.to_vec();
+        out.extend_from_slice(&FORMAT_VERSION.to_ne_bytes());
+        out.extend_from_slice(&(self.0.len() as u32).to_ne_bytes());
+        out.extend_from_slice(&self.0);
+        let crc = crc32(&out);
+        out.extend_from_slice(&crc.to_ne_bytes());
+        out
+    }
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn read_error(bytes: &[u8]) -> String {
+    Script::from_bytes(bytes)
+        .expect_err("bad file was read")
+        .to_string()
+}
+
+const CONSTANTS: [f64; 9] = [0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9];
+
+fn every_opcode() -> Vec<u8> {
+    Bytes::default()
+        .op(0x0C)
+        .string("spin")
+        .op(0x0D)
+        .u32(30)
+        .op(0x01)
+        .op(0x03)
+        .f64s(&[250.0, 250.0, 0.0])
+        .op(0x00)
+        .op(0x04)
+        .f64s(&[1.0, 360.0])
+        .op(0x01)
+        .string("turn")
+        .op(0x05)
+        .f64s(&[2.0, 0.5, -1.0])
+        .op(0x01)
+        .string("grow")
+        .op(0x06)
+        .f64s(&[-50.0, 50.0, 50.0, 100.0, 100.0, 100.0])
+        .f64s(&CONSTANTS)
+        .op(0x00)
+        .op(0x07)
+        .f64s(&[0.0, 0.0, 0.0, 75.5])
+        .f64s(&CONSTANTS)
+        .op(0x01)
+        .op(0x00)
+        .u32(40)
+        .op(0x01)
+        .string("corner")
+        .op(0x08)
+        .f64s(&[0.0, 0.0, 0.0, 20.0, 80.0])
+        .f64s(&CONSTANTS)
+        .op(0x00)
+        .op(0x00)
+        .op(0x09)
+        .f64s(&[0.0, 0.0, 0.0, 499.0, 499.0, -3.25])
+        .op(0x01)
+        .string("corner")
+        .op(0x00)
+        .op(0x02)
+        .op(0x0A)
+        .string("out.png")
+        .op(0x0B)
+        .op(0x0E)
+        .string("turn")
+        .u32(0)
+        .u32(29)
+        .f64s(&[0.0, 1.0])
+        .op(0x00)
+        .op(0x0E)
+        .string("grow")
+        .u32(10)
+        .u32(20)
+        .f64s(&[1.0, 0.25])
+        .op(0x08)
+        .f64s(&[0.25, 0.1, 0.25, 1.0])
+        .op(0x0F)
+        .f64s(&[255.0, 128.0, 0.0, 500.0, 500.0, 1000.0])
+        .op(0x10)
+        .f64s(&[50.0, 50.0, 60.0])
+        .op(0x01)
+        .string("grow")
+        .op(0x11)
+        .f64s(&[0.0, 0.0, 500.0, 0.0, 0.0, 0.0])
+        .op(0x12)
+        .f64s(&[350.0])
+        .op(0x13)
+        .op(0x03)
+        .op(0x14)
+        .op(0x01)
+        .op(0x15)
+        .string("data/cube.obj")
+        .f64s(&CONSTANTS)
+        .op(0x00)
+        .op(0x16)
+        .string("corner")
+        .op(0x17)
+        .string("turn")
+        .f64s(&[0.5])
+        .op(0x18)
+        .f64s(&[2.0])
+        .op(0x19)
+        .string("start")
+        .op(0x1A)
+        .u32(0)
+        .u32(29)
+        .string("start")
+        .string("start")
+        .op(0x1B)
+        .string("turn")
+        .op(0x01)
+        .u16(2)
+        .f64s(&[0.0, 0.0, 0.5, 29.0, 1.0, -0.25])
+        .op(0x00)
+        .framed()
+}
+
+#[test]
+fn read_then_write_is_byte_for_byte() {
+    let bytes = every_opcode();
+    let script = Script::from_bytes(&bytes).expect("failed to read intermediate");
+    assert_eq!(script.to_bytes(), bytes);
+}
+
+#[test]
+fn write_then_read_gives_the_same_script() {
+    let src = std::fs::read_to_string("data/script.dw").unwrap();
+    let mut diag = Diagnostics::with_source("data/script.dw", &src);
+    let script = Script::from_mdl(&src, &mut diag).expect("failed to parse script");
+    let bytes = script.to_bytes();
+    let again = Script::from_bytes(&bytes).expect("failed to read written script");
+    assert_eq!(again.to_bytes(), bytes);
+    assert_eq!(again, script);
+}
+
+#[test]
+fn nothing_after_the_checksum_is_read() {
+    let mut bytes = Bytes::default().op(0x01).op(0x02).op(0x00).framed();
+    let len = bytes.len();
+    bytes.extend_from_slice(&[0x01, 0x01]);
+    let script = Script::from_bytes(&bytes).unwrap();
+    assert_eq!(script.to_bytes(), &bytes[..len]);
+}
+
+#[test]
+fn checksum_is_crc32() {
+    assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+}
+
+#[test]
+fn headerless_files_are_rejected() {
+    let old = Bytes::default().op(0x01).op(0x02).op(0x00).0;
+    assert!(read_error(&old).contains("no header"));
+    assert!(read_error(b"P6\n500 500\n255\n").contains("not an MDL intermediate file"));
+}
+
+#[test]
+fn other_versions_are_rejected() {
+    let mut bytes = Bytes::default().op(0x00).framed();
+    bytes[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_ne_bytes());
+    assert!(read_error(&bytes).contains("format version"));
+}
+
+#[test]
+fn truncated_files_are_rejected() {
+    let bytes = every_opcode();
+    assert!(read_error(&bytes[..bytes.len() - 2]).contains("cut off"));
+    assert!(read_error(&bytes[..bytes.len() / 2]).contains("cut off"));
+    assert!(read_error(&bytes[..8]).contains("cut off"));
+}
+
+#[test]
+fn corrupt_files_are_rejected() {
+    let mut bytes = every_opcode();
+    //flip a bit in the x of the first move
+    bytes[10 + 6 + 5 + 1 + 1] ^= 0x10;
+    assert!(read_error(&bytes).contains("corrupt"));
+}
+
+#[test]
+fn unknown_opcodes_are_rejected() {
+    let bytes = Bytes::default().op(0x01).op(0x7F).op(0x00).framed();
+    assert!(read_error(&bytes).contains("unknown opcode 0x7F"));
+}
+
+#[test]
+fn disassembly_has_one_line_per_command_at_its_offset() {
+    let bytes = every_opcode();
+    let script = Script::from_bytes(&bytes).unwrap();
+    let text = script.disassemble();
+    let lines: Vec<_> = text.lines().collect();
+    assert_eq!(lines.len(), 29);
+    assert_eq!(lines[0], "0x000a  basename spin");
+    assert_eq!(lines[3], "0x0016  move 250 250 0");
+    assert_eq!(lines[4], "0x0030  rotate y 360 turn");
+    assert!(lines[6].starts_with("0x0066  box -50 50 50 100 100 100  // ka 0.1 0.2 0.3"));
+    assert!(lines[7].contains("  sphere resolution 40 0 0 0 75.5 corner  // "));
+    assert!(lines[8].contains("  torus 0 0 0 20 80  // "));
+    assert!(lines[9].ends_with("  line 0 0 0 corner 499 499 -3.25"));
+    assert!(lines[13].ends_with("  vary turn 0 29 0 1"));
+    assert!(lines[14].ends_with("  vary grow 10 20 1 0.25 cubic-bezier 0.25 0.1 0.25 1"));
+    assert!(lines[20].ends_with("  resolution auto"));
+    assert!(lines[21].contains("  mesh :data/cube.obj  // ka 0.1 0.2 0.3"));
+    assert!(lines[22].ends_with("  save_coord_system corner"));
+    assert!(lines[23].ends_with("  set turn 0.5"));
+    assert!(lines[24].ends_with("  setknobs 2"));
+    assert!(lines[25].ends_with("  save_knobs start"));
+    assert!(lines[26].ends_with("  tween 0 29 start start"));
+    assert!(lines[27].ends_with("  spline turn hermite 0 0 0.5 29 1 -0.25"));
+    //every offset points at the opcode of its command
+    let ops: Vec<u8> = lines
+        .iter()
+        .map(|l| bytes[usize::from_str_radix(&l[2..6], 16).unwrap()])
+        .collect();
+    assert_eq!(
+        ops,
+        [
+            0x0C, 0x0D, 0x01, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x02, 0x0A, 0x0B, 0x0E,
+            0x0E, 0x0F, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1A, 0x1B,
+            0x00
+        ]
+    );
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use common::{draw, problems};
+use graphics::{Diagnostics, Easing, Engine, KnobError, RGB8Color, Script};
+
+const BOX: &str = "box 0 0 0 10 10 10";
+
+#[test]
+fn set_knobs_scale_transformations() {
+    let set = draw(&format!("set half 0.5\nmove 100 100 0 half\n{}", BOX));
+    assert!(set.iter().any(|&b| b != 0));
+    assert!(set == draw(&format!("move 50 50 0\n{}", BOX)));
+}
+
+#[test]
+fn knobs_are_set_in_order() {
+    let set = draw(&format!(
+        "set k 1\nmove 20 20 0 k\nset k 2\nmove 20 20 0 k\n{}",
+        BOX
+    ));
+    assert!(set == draw(&format!("move 60 60 0\n{}", BOX)));
+    //before it is set a knob has the last value it is set to
+    let early = draw(&format!("move 20 20 0 k\nset k 3\n{}", BOX));
+    assert!(early == draw(&format!("move 60 60 0\n{}", BOX)));
+}
+
+#[test]
+fn setknobs_sets_every_knob() {
+    let set = draw(&format!(
+        "set a 5\nsetknobs 2\nmove 10 0 0 a\nmove 0 20 0 b\n{}",
+        BOX
+    ));
+    assert!(set == draw(&format!("move 20 40 0\n{}", BOX)));
+}
+
+#[test]
+fn tweens_go_between_saved_lists() {
+    let mut eng = Engine::<RGB8Color>::with_screen_dims(10, 10);
+    eng.add_knob("a");
+    eng.add_knob("b");
+    eng.set_knobs(0.0);
+    eng.save_knobs("start");
+    eng.set_knob("a", 10.0);
+    eng.set_knob("b", -4.0);
+    eng.save_knobs("end");
+    eng.add_tween(2, 6, "start", "end", 10).unwrap();
+    assert_eq!(eng.get_knob_val("a".into(), 2), 0.0);
+    assert_eq!(eng.get_knob_val("a".into(), 4), 5.0);
+    assert_eq!(eng.get_knob_val("b".into(), 5), -3.0);
+    assert_eq!(eng.get_knob_val("a".into(), 6), 10.0);
+    //outside the tween a knob holds the values at its ends, even once it is set
+    eng.set_knob("a", 7.0);
+    assert_eq!(eng.get_knob_val("a".into(), 0), 0.0);
+    assert_eq!(eng.get_knob_val("a".into(), 9), 10.0);
+    assert_eq!(eng.get_knob_val("unheard_of".into(), 3), 1.0);
+}
+
+#[test]
+fn tweened_lists_must_be_saved_first() {
+    let text =
+        problems("frames 10\nbasename t\nsave_knobs a\ntween 0 9 a b\nsave_knobs b\nmove 1 1 1 k");
+    assert!(text.contains("E0019"), "{}", text);
+    assert!(text.contains("`b`"), "{}", text);
+    assert!(!text.contains("`a`"), "{}", text);
+    //the tween gives `k` a value
+    assert!(!text.contains("E0012"), "{}", text);
+
+    let text = problems("save_knobs a\ntween 0 9 a a");
+    assert!(text.contains("E0011"), "{}", text);
+
+    //a tween with neither frames nor a saved list has both reported
+    let text = problems("tween 0 9 a a");
+    assert!(text.contains("E0011"), "{}", text);
+    assert!(text.contains("E0019"), "{}", text);
+}
+
+#[test]
+fn set_knobs_count_as_given_in_animations() {
+    let text = problems("frames 10\nbasename t\nset k 2\nmove 1 1 1 k\nmove 1 1 1 j");
+    assert_eq!(text.matches("E0012").count(), 1, "{}", text);
+    assert!(text.contains("`j`"), "{}", text);
+}
+
+#[test]
+fn knobs_hold_their_values_between_varies() {
+    let mut eng = Engine::<RGB8Color>::with_screen_dims(10, 10);
+    eng.add_vary("k".into(), 3, 5, 0.0, 1.0, Easing::Linear, 20)
+        .unwrap();
+    eng.add_vary("k".into(), 10, 12, 4.0, 2.0, Easing::Linear, 20)
+        .unwrap();
+    let values: Vec<_> = (0..20).map(|f| eng.get_knob_val("k".into(), f)).collect();
+    assert_eq!(
+        values,
+        [
+            0.0, 0.0, 0.0, 0.0, 0.5, 1.0, 1.0, 1.0, 1.0, 1.0, 4.0, 3.0, 2.0, 2.0, 2.0, 2.0, 2.0,
+            2.0, 2.0, 2.0
+        ]
+    );
+}
+
+#[test]
+fn frame_ranges_must_fit_the_animation() {
+    let text = problems("frames 10\nbasename t\nvary k 5 2 0 1\nmove 1 1 1 k");
+    assert!(text.contains("E0022"), "{}", text);
+    assert!(text.contains("before it starts"), "{}", text);
+    let text = problems("frames 10\nbasename t\nvary k 0 10 0 1\nmove 1 1 1 k");
+    assert!(text.contains("E0022"), "{}", text);
+    assert!(text.contains("the last is 9"), "{}", text);
+    let text = problems("frames 10\nbasename t\nsave_knobs a\ntween 3 12 a a");
+    assert!(text.contains("E0022"), "{}", text);
+    //the last frames command is the one used
+    let text = problems("frames 5\nbasename t\nvary k 0 9 0 1\nmove 1 1 1 k\nframes 10");
+    assert!(!text.contains("E0022"), "{}", text);
+}
+
+#[test]
+fn overlapping_varies_are_warned_about() {
+    let src = "frames 30\nbasename t\nvary k 0 10 0 1\nvary k 10 20 1 0\nvary j 5 15 0 1\n\
+               vary k 15 25 0 2\nmove 1 1 1 k\nmove 1 1 1 j";
+    let mut diag = Diagnostics::with_source("test.mdl", src);
+    //it is only a warning, the script still runs
+    assert!(Script::from_mdl(src, &mut diag).is_some());
+    let text = diag.to_string();
+    assert_eq!(text.matches("W0004").count(), 1, "{}", text);
+    assert!(
+        text.contains("from frame 15 to 25 overlaps the one from 10 to 20"),
+        "{}",
+        text
+    );
+}
+
+#[test]
+fn bad_ranges_and_lists_are_errors() {
+    let mut eng = Engine::<RGB8Color>::with_screen_dims(10, 10);
+    let backwards = eng.add_vary("k".into(), 5, 2, 0.0, 1.0, Easing::Linear, 10);
+    assert_eq!(
+        backwards,
+        Err(KnobError::FrameRange {
+            start: 5,
+            end: 2,
+            total: 10
+        })
+    );
+    let past_the_end = eng.add_vary("k".into(), 0, 10, 0.0, 1.0, Easing::Linear, 10);
+    assert!(past_the_end
+        .unwrap_err()
+        .to_string()
+        .contains("has 10 frames"));
+    //nothing is kept from a vary that failed
+    assert_eq!(eng.get_knob_val("k".into(), 3), 1.0);
+
+    eng.save_knobs("a");
+    let unsaved = eng.add_tween(0, 9, "a", "b", 10);
+    assert_eq!(unsaved, Err(KnobError::UnknownList("b".into())));
+}
@@ -0,0 +1,412 @@
+use graphics::{Diagnostics, Engine, Light, Mesh, MeshError, RGB8Color, Script, Space};
+
+#[test]
+fn faces_with_more_corners_are_split_into_triangles() {
+    let mesh =
+        Mesh::from_obj("v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv -1 1 0\nf 1 2 3 4 5\n").unwrap();
+    assert_eq!(mesh.faces.len(), 3);
+    let positions: Vec<_> = mesh.faces.iter().map(|f| f.map(|c| c.position)).collect();
+    assert_eq!(positions, [[0, 1, 2], [0, 2, 3], [0, 3, 4]]);
+}
+
+#[test]
+fn negative_indices_count_back_from_the_last_one_read() {
+    let src = "v 0 0 0\nv 1 0 0\nv 1 1 0\nvt 0.5\nvn 0 0 1\nf -3/-1/-1 -2/1/1 -1//-1\nv 5 5 5\nf -4 -3 -1";
+    let mesh = Mesh::from_obj(src).unwrap();
+    assert_eq!(mesh.uvs, [(0.5, 0.0)]);
+    let [a, b, c] = mesh.faces[0];
+    assert_eq!((a.position, a.uv, a.normal), (0, Some(0), Some(0)));
+    assert_eq!((b.position, b.uv, b.normal), (1, Some(0), Some(0)));
+    assert_eq!((c.position, c.uv, c.normal), (2, None, Some(0)));
+    assert_eq!(mesh.faces[1].map(|c| c.position), [0, 1, 3]);
+}
+
+#[test]
+fn anything_that_is_not_geometry_is_skipped() {
+    let src = "# a comment\nmtllib a.mtl\no thing\ng part\nusemtl red\ns 1\n\
+               v 0 0 0 # trailing\nv 2 0 0 2\nv 0 1 0\nl 1 2\nf 1 2 3\n";
+    let mesh = Mesh::from_obj(src).unwrap();
+    assert_eq!(
+        mesh.positions,
+        [(0.0, 0.0, 0.0), (1.0, 0.0, 0.0), (0.0, 1.0, 0.0)]
+    );
+    assert_eq!(mesh.faces.len(), 1);
+}
+
+#[test]
+fn bad_files_say_which_line_is_wrong() {
+    for (src, line, message) in [
+        ("v 0 0 0\nv 0 zero 0", 2, "not a number"),
+        ("v 0 0", 1, "needs 3 numbers"),
+        ("v 0 0 0\nv 1 0 0\n\nf 1 2", 4, "at least 3 corners"),
+        ("v 0 0 0\nf 1 1 2", 2, "out of range"),
+        ("v 0 0 0\nf 1 1 -2", 2, "out of range"),
+        ("v 0 0 0\nf 0 1 1", 2, "out of range"),
+        ("v 0 0 0\nf 1//1 1 1", 2, "normal index 1 is out of range"),
+        ("v 0 0 0\nf 1/1/1/1 1 1", 2, "too many parts"),
+    ] {
+        match Mesh::from_obj(src) {
+            Err(MeshError::Parse {
+                line: l,
+                message: m,
+            }) => {
+                assert_eq!(l, line, "{}", src);
+                assert!(m.contains(message), "{}: {}", src, m);
+            }
+            other => panic!("{} gave {:?}", src, other),
+        }
+    }
+}
+
+#[test]
+fn corners_sharing_a_position_and_normal_share_a_vertex() {
+    let mesh = Mesh::load("data/cube.obj").unwrap();
+    assert_eq!(mesh.faces.len(), 12);
+    let mut space = Space::<RGB8Color>::new();
+    mesh.add_to(&mut space);
+    assert_eq!(space.tri_count(), 12);
+    //each corner of the cube is on three faces with different normals
+    assert_eq!(space.vertex_count(), 24);
+}
+
+#[test]
+fn only_known_formats_are_read() {
+    assert!(matches!(
+        Mesh::load("data/script.dw"),
+        Err(MeshError::UnknownFormat(ext)) if ext == "dw"
+    ));
+    assert!(matches!(
+        Mesh::load("data/missing.obj"),
+        Err(MeshError::Io(_))
+    ));
+}
+
+///an engine turned so three sides of a cube show, the light is far enough away that it shines
+///the same way on every corner of a face no matter how the face is split into triangles
+fn turned_engine() -> Engine<RGB8Color> {
+    let mut eng = Engine::<RGB8Color>::with_screen_dims(500, 500);
+    eng.add_light(Light::new((5e6, 1e7, 1e7), (255, 255, 255).into()));
+    eng.set_constants([0.1, 0.1, 0.1, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5]);
+    eng.move_sys(250.0, 250.0, 0.0);
+    eng.rotate_sys(0.0, 30.0);
+    eng.rotate_sys(1.0, 40.0);
+    eng
+}
+
+#[test]
+fn a_cube_mesh_looks_like_a_box() {
+    let mut boxed = turned_engine();
+    boxed.add_box((-100.0, 100.0, 100.0), 200.0, 200.0, 200.0);
+    boxed.apply_sys();
+    boxed.draw_space();
+
+    let mut meshed = turned_engine();
+    meshed.scale_sys(200.0, 200.0, 200.0);
+    meshed.add_mesh_file("data/cube.obj").unwrap();
+    meshed.apply_sys();
+    meshed.draw_space();
+
+    let lit = |e: &Engine<RGB8Color>| e.ppm_byte_vec().iter().filter(|&&b| b != 0).count();
+    assert!(lit(&boxed) > 0);
+    //a face wound the wrong way would be culled or lit from the wrong side
+    assert!(boxed.ppm_byte_vec() == meshed.ppm_byte_vec());
+}
+
+#[test]
+fn a_missing_mesh_stops_the_script() {
+    let src = "mesh :data/missing.obj\nsphere 0 0 0 10";
+    let mut diag = Diagnostics::with_source("test.mdl", src);
+    let script = Script::from_mdl(src, &mut diag).expect("failed to parse script");
+    let mut eng = Engine::<RGB8Color>::with_screen_dims(50, 50);
+    script.exec(&mut eng, &mut diag);
+    assert!(diag.to_string().contains("E0017"), "{}", diag);
+    assert!(diag.to_string().contains("data/missing.obj"), "{}", diag);
+}
+
+///a binary STL file with the given facets, each a normal then three corners
+fn binary_stl(facets: &[[[f32; 3]; 4]]) -> Vec<u8> {
+    let mut bytes = b"solid but actually binary".to_vec();
+    bytes.resize(80, 0);
+    bytes.extend((facets.len() as u32).to_le_bytes());
+    for facet in facets {
+        for n in facet.iter().flatten() {
+            bytes.extend(n.to_le_bytes());
+        }
+        bytes.extend(0u16.to_le_bytes());
+    }
+    bytes
+}
+
+const TETRAHEDRON: [[[f32; 3]; 4]; 4] = [
+    [
+        [0.0, 0.0, -1.0],
+        [0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0],
+        [1.0, 0.0, 0.0],
+    ],
+    [
+        [0.0, -1.0, 0.0],
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0],
+    ],
+    [
+        [-1.0, 0.0, 0.0],
+        [0.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0],
+        [0.0, 1.0, 0.0],
+    ],
+    [
+        [1.0, 1.0, 1.0],
+        [1.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0],
+        [0.0, 0.0, 1.0],
+    ],
+];
+
+#[test]
+fn binary_and_text_stl_give_the_same_mesh() {
+    let mut text = String::from("solid tetrahedron\n");
+    for [n, p, q, r] in TETRAHEDRON {
+        text += &format!(
+            "  facet normal {} {} {}\n    outer loop\n",
+            n[0], n[1], n[2]
+        );
+        for c in [p, q, r] {
+            text += &format!("      vertex {} {} {}\n", c[0], c[1], c[2]);
+        }
+        text += "    endloop\n  endfacet\n";
+    }
+    text += "endsolid tetrahedron\n";
+    let binary = Mesh::from_stl(&binary_stl(&TETRAHEDRON)).unwrap();
+    assert_eq!(Mesh::from_stl(text.as_bytes()).unwrap(), binary);
+    //corners at the same spot are joined
+    assert_eq!(binary.positions.len(), 4);
+    assert_eq!(binary.faces.len(), 4);
+}
+
+#[test]
+fn stl_facets_are_turned_to_face_their_normal() {
+    let mut flipped = TETRAHEDRON;
+    flipped[0] = [
+        [0.0, 0.0, -1.0],
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0],
+    ];
+    //and a zero normal leaves a facet how it is
+    flipped[1][0] = [0.0, 0.0, 0.0];
+    let right = Mesh::from_stl(&binary_stl(&TETRAHEDRON)).unwrap();
+    let turned = Mesh::from_stl(&binary_stl(&flipped)).unwrap();
+    let positions = |m: &Mesh, f: usize| m.faces[f].map(|c| m.positions[c.position]);
+    assert_eq!(
+        {
+            let [p, q, r] = positions(&turned, 0);
+            [r, p, q]
+        },
+        positions(&right, 0)
+    );
+    assert_eq!(positions(&turned, 1), positions(&right, 1));
+}
+
+#[test]
+fn stl_with_the_wrong_size_is_rejected() {
+    let mut bytes = binary_stl(&TETRAHEDRON);
+    bytes.pop();
+    let err = Mesh::from_stl(&bytes).unwrap_err();
+    assert!(matches!(err, MeshError::Binary(_)), "{:?}", err);
+    assert!(err.to_string().contains("4 facets"), "{}", err);
+
+    let err = Mesh::from_stl(b"solid x\nfacet normal 0 0 1\nvertex 0 0\n").unwrap_err();
+    assert!(matches!(err, MeshError::Parse { line: 3, .. }), "{:?}", err);
+}
+
+const PLY_HEADER: &str = "comment a square and a triangle\n\
+                          element vertex 5\n\
+                          property float x\nproperty float y\nproperty float z\n\
+                          property float nx\nproperty float ny\nproperty float nz\n\
+                          property float s\nproperty float t\n\
+                          element face 2\nproperty list uchar int vertex_indices\n\
+                          element edge 1\nproperty int vertex1\nproperty int vertex2\n\
+                          end_header\n";
+
+const PLY_VERTICES: [[f32; 8]; 5] = [
+    [0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+    [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0],
+    [1.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+    [0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0],
+    [2.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.5, 0.5],
+];
+
+const PLY_FACES: [&[i32]; 2] = [&[0, 1, 2, 3], &[1, 4, 2]];
+
+fn binary_ply(format: &str, to_bytes: fn(f32) -> [u8; 4], int: fn(i32) -> [u8; 4]) -> Vec<u8> {
+    let mut bytes = format!("ply\nformat {} 1.0\n{}", format, PLY_HEADER).into_bytes();
+    for v in PLY_VERTICES.iter().flatten() {
+        bytes.extend(to_bytes(*v));
+    }
+    for f in PLY_FACES {
+        bytes.push(f.len() as u8);
+        for &i in f {
+            bytes.extend(int(i));
+        }
+    }
+    bytes.extend(int(0));
+    bytes.extend(int(1));
+    bytes
+}
+
+#[test]
+fn ply_in_any_format_gives_the_same_mesh() {
+    let mut text = format!("ply\nformat ascii 1.0\n{}", PLY_HEADER);
+    for v in PLY_VERTICES {
+        text += &v.map(|n| n.to_string()).join(" ");
+        text += "\n";
+    }
+    for f in PLY_FACES {
+        text += &format!(
+            "{} {}\n",
+            f.len(),
+            f.iter()
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+    }
+    text += "0 1\n";
+    let mesh = Mesh::from_ply(text.as_bytes()).unwrap();
+    assert_eq!(mesh.positions.len(), 5);
+    assert_eq!(mesh.normals.len(), 5);
+    assert_eq!(mesh.uvs[4], (0.5, 0.5));
+    assert_eq!(mesh.faces.len(), 3);
+    assert_eq!(mesh.faces[2].map(|c| c.position), [1, 4, 2]);
+    assert_eq!(mesh.faces[2][1].normal, Some(4));
+
+    let little = binary_ply("binary_little_endian", f32::to_le_bytes, i32::to_le_bytes);
+    let big = binary_ply("binary_big_endian", f32::to_be_bytes, i32::to_be_bytes);
+    assert_eq!(Mesh::from_ply(&little).unwrap(), mesh);
+    assert_eq!(Mesh::from_ply(&big).unwrap(), mesh);
+}
+
+#[test]
+fn bad_ply_files_are_rejected() {
+    let text = |body: &str| format!("ply\nformat ascii 1.0\n{}{}", PLY_HEADER, body);
+    for (src, line) in [
+        (
+            "ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\n".into(),
+            5,
+        ),
+        ("plyx\n".into(), 1),
+        (
+            "ply\nformat ascii 1.0\nproperty float x\nend_header\n".into(),
+            3,
+        ),
+        (
+            "ply\nformat ascii 1.0\nelement vertex 1\nproperty float x y\nend_header\n".into(),
+            4,
+        ),
+        (
+            "ply\nformat ascii 1.0\nelement vertex 1\nproperty quad x\nend_header\n".into(),
+            4,
+        ),
+        (text("0 0 0 0 0 1 0\n"), 19),
+        (text("0 0 0 0 0 1 0 0 1\n"), 19),
+        (text(&"0 0 0 0 0 1 0 0\n".repeat(5)), 24),
+        (
+            text(&format!("{}3 0 1 5\n", "0 0 0 0 0 1 0 0\n".repeat(5))),
+            24,
+        ),
+        (
+            text(&format!("{}2 0 1\n", "0 0 0 0 0 1 0 0\n".repeat(5))),
+            24,
+        ),
+        (
+            text(&format!("{}3 0 1 -1\n", "0 0 0 0 0 1 0 0\n".repeat(5))),
+            24,
+        ),
+    ] {
+        match Mesh::from_ply(src.as_bytes()) {
+            Err(MeshError::Parse { line: l, .. }) => assert_eq!(l, line, "{}", src),
+            other => panic!("{} gave {:?}", src, other),
+        }
+    }
+    let mut cut = binary_ply("binary_little_endian", f32::to_le_bytes, i32::to_le_bytes);
+    cut.truncate(cut.len() - 6);
+    assert!(matches!(Mesh::from_ply(&cut), Err(MeshError::Binary(_))));
+}
+
+#[test]
+fn meshes_are_picked_by_extension() {
+    let dir = std::env::temp_dir().join(format!("graphics-mesh-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let stl = dir.join("tetrahedron.STL");
+    std::fs::write(&stl, binary_stl(&TETRAHEDRON)).unwrap();
+    let mut eng = Engine::<RGB8Color>::with_screen_dims(50, 50);
+    eng.add_mesh_file(stl.to_str().unwrap()).unwrap();
+    let ply = dir.join("shapes.ply");
+    std::fs::write(
+        &ply,
+        binary_ply("binary_big_endian", f32::to_be_bytes, i32::to_be_bytes),
+    )
+    .unwrap();
+    assert_eq!(Mesh::load(&ply).unwrap().faces.len(), 3);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn written_meshes_read_back_the_same() {
+    let cube = Mesh::load("data/cube.obj").unwrap();
+    assert_eq!(Mesh::from_obj(&cube.to_obj()).unwrap(), cube);
+
+    let tetrahedron = Mesh::from_stl(&binary_stl(&TETRAHEDRON)).unwrap();
+    let stl = tetrahedron.to_stl();
+    assert_eq!(Mesh::from_stl(&stl).unwrap(), tetrahedron);
+    //the normals written are worked out from the corners
+    let normal: Vec<_> = stl[84..96]
+        .chunks(4)
+        .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+        .collect();
+    assert_eq!(normal, [0.0, 0.0, -1.0]);
+}
+
+#[test]
+fn exported_scenes_keep_every_shape_where_it_was_drawn() {
+    let mut eng = Engine::<RGB8Color>::with_screen_dims(50, 50);
+    eng.add_box((0.0, 0.0, 0.0), 1.0, 1.0, 1.0);
+    eng.apply_sys();
+    eng.draw_space();
+    eng.clear_tris();
+    assert!(eng.export().is_none());
+
+    eng.start_export();
+    eng.move_sys(10.0, 0.0, 0.0);
+    eng.add_box((0.0, 0.0, 0.0), 1.0, 1.0, 1.0);
+    eng.apply_sys();
+    eng.draw_space();
+    eng.clear_tris();
+    eng.scale_sys(2.0, 2.0, 2.0);
+    eng.add_mesh_file("data/cube.obj").unwrap();
+    eng.apply_sys();
+    eng.draw_space();
+    eng.clear_tris();
+
+    let scene = eng.take_export().unwrap();
+    assert_eq!(scene.faces.len(), 24);
+    let xs = scene.positions.iter().map(|p| p.0);
+    assert_eq!(xs.clone().fold(f64::INFINITY, f64::min), 9.0);
+    assert_eq!(xs.fold(0.0, f64::max), 11.0);
+    //only the cube brought normals, and they stay a unit long after the scale
+    assert_eq!(scene.normals.len(), 24);
+    assert!(scene.normals.contains(&(0.0, 0.0, 1.0)));
+    assert!(eng.export().is_none());
+}
+
+#[test]
+fn only_known_formats_are_written() {
+    let path = std::env::temp_dir().join("graphics-mesh-export.ply");
+    assert!(matches!(
+        Mesh::default().save(&path),
+        Err(MeshError::UnknownFormat(ext)) if ext == "ply"
+    ));
+    assert!(!path.exists());
+}
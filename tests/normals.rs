@@ -0,0 +1,104 @@
+use graphics::draw::Tessellation;
+use graphics::space::Point;
+use graphics::{add_sphere, add_torus, cross, dot, normalize, scale_matrix, sub};
+use graphics::{Modtrix, RGB8Color, Space};
+
+///whether `a` and `b` point the same way, whatever their lengths
+fn same_way(a: Point, b: Point) -> bool {
+    let (a, b) = (normalize(a), normalize(b));
+    let c = cross(a, b);
+    dot(c, c) < 1e-12 && dot(a, b) > 0.0
+}
+
+///every vertex in the space with its normal
+fn vertices(space: &Space<RGB8Color>) -> Vec<(Point, Point)> {
+    (0..space.vertex_count() as u32)
+        .map(|i| {
+            let (p, n) = space.vertex(i);
+            (p, n.expect("every vertex should have a normal"))
+        })
+        .collect()
+}
+
+#[test]
+fn normal_matrix_is_the_inverse_transpose() {
+    let m = Modtrix::from([
+        [2.0, 1.0, 0.0, 5.0],
+        [0.0, 3.0, -1.0, -2.0],
+        [1.0, 0.0, 0.5, 7.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]);
+    let n = m.normal_matrix();
+    //the transpose of m times its inverse transpose is the identity
+    for i in 0..3 {
+        for j in 0..3 {
+            let entry: f64 = (0..3).map(|k| m.store[k][i] * n.store[k][j]).sum();
+            let want = if i == j { 1.0 } else { 0.0 };
+            assert!((entry - want).abs() < 1e-12, "{:?}", n);
+        }
+    }
+    //normals are directions, moving doesn't change them
+    assert_eq!(n.store[3], [0.0, 0.0, 0.0, 1.0]);
+    assert!((0..3).all(|i| n.store[i][3] == 0.0));
+    //a matrix which flattens everything leaves normals alone
+    let flat = scale_matrix!(1.0, 0.0, 1.0).normal_matrix();
+    assert_eq!(flat.store, Modtrix::IDENT.store);
+}
+
+#[test]
+fn sphere_normals_point_out_from_the_center() {
+    let center = (3.0, -2.0, 1.0);
+    let mut space = Space::<RGB8Color>::new();
+    add_sphere(
+        center.0,
+        center.1,
+        center.2,
+        5.0,
+        Tessellation::Steps(12),
+        &mut space,
+    );
+    let vertices = vertices(&space);
+    assert!(!vertices.is_empty());
+    for (p, n) in vertices {
+        assert!(same_way(n, sub(p, center)), "{:?} at {:?}", n, p);
+    }
+}
+
+#[test]
+fn sphere_normals_stay_square_to_the_surface_when_scaled_unevenly() {
+    let mut space = Space::<RGB8Color>::new();
+    add_sphere(0.0, 0.0, 0.0, 1.0, Tessellation::Steps(12), &mut space);
+    space.apply(&scale_matrix!(4.0, 1.0, 0.5));
+    for (p, n) in vertices(&space) {
+        //the ellipsoid (x/4)^2 + y^2 + (2z)^2 = 1 faces along the gradient of its equation,
+        //which is not straight out from the center
+        let gradient = (p.0 / 16.0, p.1, p.2 * 4.0);
+        assert!(same_way(n, gradient), "{:?} at {:?}", n, p);
+    }
+}
+
+#[test]
+fn torus_normals_point_out_from_the_middle_of_the_tube() {
+    let (tube, ring) = (1.0, 4.0);
+    let mut space = Space::<RGB8Color>::new();
+    add_torus(
+        0.0,
+        0.0,
+        0.0,
+        tube,
+        ring,
+        Tessellation::Steps(10),
+        &mut space,
+    );
+    space.apply(&scale_matrix!(1.0, 3.0, 1.0));
+    for (p, n) in vertices(&space) {
+        //undo the scale to find the point on the torus before it, the middle of the tube there
+        //is on the ring around the y axis
+        let q = (p.0, p.1 / 3.0, p.2);
+        let out = ring / (q.0 * q.0 + q.2 * q.2).sqrt();
+        let from_middle = sub(q, (q.0 * out, 0.0, q.2 * out));
+        //then the scale takes normals by its inverse transpose
+        let want = (from_middle.0, from_middle.1 / 3.0, from_middle.2);
+        assert!(same_way(n, want), "{:?} at {:?}", n, p);
+    }
+}
@@ -0,0 +1,187 @@
+mod common;
+
+use common::problems;
+use graphics::{Diagnostics, Script};
+
+///the commands of a script as the disassembler prints them, without the byte offsets
+fn commands(src: &str) -> Vec<String> {
+    let mut diag = Diagnostics::with_source("test.mdl", src);
+    let script = Script::from_mdl(src, &mut diag);
+    assert!(diag.is_empty(), "{}", diag);
+    script
+        .unwrap()
+        .disassemble()
+        .lines()
+        .map(|l| l.split_once("  ").expect("no offset").1.to_string())
+        .collect()
+}
+
+const SHINY: &str = "constants shiny 0.1 0.2 0.3 0.4 0.5 0.6 0.7 0.8 0.9";
+
+///a line for every command in the spec and how it is disassembled, shapes are followed by the
+///reflection constants they were given
+const EVERY_COMMAND: &[(&str, &str)] = &[
+    ("frames 10", "frames 10"),
+    ("basename out/anim", "basename out/anim"),
+    ("push", "push"),
+    ("move 1 2 3", "move 1 2 3"),
+    ("scale 2 2 2 k", "scale 2 2 2 k"),
+    ("rotate x 30", "rotate x 30"),
+    ("rotate y -45.5 k", "rotate y -45.5 k"),
+    ("rotate z .5", "rotate z 0.5"),
+    ("save_coord_system world", "save_coord_system world"),
+    ("sphere 0 0 0 10", "sphere 0 0 0 10  // ka 0.1 0.1 0.1  kd 0.5 0.5 0.5  ks 0.5 0.5 0.5"),
+    ("sphere shiny 0 0 0 10", "sphere 0 0 0 10  // ka 0.1 0.4 0.7  kd 0.2 0.5 0.8  ks 0.3 0.6 0.9"),
+    (
+        "sphere shiny 0 0 0 10 world",
+        "sphere 0 0 0 10 world  // ka 0.1 0.4 0.7  kd 0.2 0.5 0.8  ks 0.3 0.6 0.9",
+    ),
+    (
+        "sphere resolution 12 0 0 0 10",
+        "sphere resolution 12 0 0 0 10  // ka 0.1 0.1 0.1  kd 0.5 0.5 0.5  ks 0.5 0.5 0.5",
+    ),
+    (
+        "sphere shiny resolution auto 0 0 0 10 world",
+        "sphere resolution auto 0 0 0 10 world  // ka 0.1 0.4 0.7  kd 0.2 0.5 0.8  ks 0.3 0.6 0.9",
+    ),
+    ("torus 0 0 0 1 5", "torus 0 0 0 1 5  // ka 0.1 0.1 0.1  kd 0.5 0.5 0.5  ks 0.5 0.5 0.5"),
+    (
+        "torus resolution tolerance 0.5 0 0 0 1 5",
+        "torus resolution tolerance 0.5 0 0 0 1 5  // ka 0.1 0.1 0.1  kd 0.5 0.5 0.5  ks 0.5 0.5 0.5",
+    ),
+    (
+        "torus shiny 0 0 0 1 5 world",
+        "torus 0 0 0 1 5 world  // ka 0.1 0.4 0.7  kd 0.2 0.5 0.8  ks 0.3 0.6 0.9",
+    ),
+    ("box 0 0 0 1 2 3", "box 0 0 0 1 2 3  // ka 0.1 0.1 0.1  kd 0.5 0.5 0.5  ks 0.5 0.5 0.5"),
+    (
+        "box shiny 0 0 0 1 2 3 world",
+        "box 0 0 0 1 2 3 world  // ka 0.1 0.4 0.7  kd 0.2 0.5 0.8  ks 0.3 0.6 0.9",
+    ),
+    ("line 0 0 0 1 1 1", "line 0 0 0 1 1 1"),
+    ("line shiny 0 0 0 world 1 1 1 world", "line 0 0 0 world 1 1 1 world"),
+    (
+        "mesh :data/cube.obj",
+        "mesh :data/cube.obj  // ka 0.1 0.1 0.1  kd 0.5 0.5 0.5  ks 0.5 0.5 0.5",
+    ),
+    (
+        "mesh shiny :data/cube.obj world",
+        "mesh :data/cube.obj world  // ka 0.1 0.4 0.7  kd 0.2 0.5 0.8  ks 0.3 0.6 0.9",
+    ),
+    ("pop", "pop"),
+    ("vary k 0 9 0 1", "vary k 0 9 0 1"),
+    ("vary j 0 9 0 1 ease-in", "vary j 0 9 0 1 ease-in"),
+    ("set a 2", "set a 2"),
+    ("save_knobs start", "save_knobs start"),
+    ("setknobs 3", "setknobs 3"),
+    ("save_knobs end", "save_knobs end"),
+    ("tween 0 9 start end", "tween 0 9 start end"),
+    ("spline s catmull-rom 0 0 9 1", "spline s catmull-rom 0 0 9 1"),
+    ("light 255 255 255 1 2 3", "light 255 255 255 1 2 3"),
+    ("ambient 50 50 50 k", "ambient 50 50 50 k"),
+    ("shading phong", "shading phong"),
+    ("resolution 20", "resolution 20"),
+    ("resolution auto", "resolution auto"),
+    ("resolution tolerance 1.5", "resolution tolerance 1.5"),
+    ("camera 0 0 10 0 0 0", "camera 0 0 10 0 0 0"),
+    ("focal 100", "focal 100"),
+    ("save out.png", "save out.png"),
+    ("display", "display"),
+];
+
+#[test]
+fn every_command_in_the_spec_is_read() {
+    let lines: Vec<_> = EVERY_COMMAND.iter().map(|(line, _)| *line).collect();
+    let mut expected: Vec<_> = EVERY_COMMAND.iter().map(|(_, dis)| *dis).collect();
+    expected.push("// end");
+    assert_eq!(
+        commands(&format!("{}\n{}", SHINY, lines.join("\n"))),
+        expected
+    );
+}
+
+#[test]
+fn comments_are_skipped() {
+    let with =
+        "// at the start\npush // after a command\n\n//move 1 2 3\nmove 1 2 3 //k\n// at the end";
+    assert_eq!(commands(with), commands("push\nmove 1 2 3"));
+    assert_eq!(commands(with), ["push", "move 1 2 3", "// end"]);
+}
+
+#[test]
+fn knobs_are_optional() {
+    let src = "frames 2\nbasename b\nvary k 0 1 0 1\nmove 1 2 3\nmove 1 2 3 k\nscale 2 2 2\nscale 2 2 2 k\nrotate y 5\nrotate y 5 k\nambient 1 2 3\nambient 1 2 3 k";
+    let found = commands(src);
+    assert_eq!(
+        found[3..11],
+        [
+            "move 1 2 3",
+            "move 1 2 3 k",
+            "scale 2 2 2",
+            "scale 2 2 2 k",
+            "rotate y 5",
+            "rotate y 5 k",
+            "ambient 1 2 3",
+            "ambient 1 2 3 k"
+        ]
+    );
+}
+
+#[test]
+fn coord_systems_are_optional() {
+    let src = "save_coord_system c\nsphere 0 0 0 1\nsphere 0 0 0 1 c\nline 0 0 0 1 1 1 c";
+    let found = commands(src);
+    assert!(found[1].starts_with("sphere 0 0 0 1  //"), "{}", found[1]);
+    assert!(found[2].starts_with("sphere 0 0 0 1 c  //"), "{}", found[2]);
+    assert_eq!(found[3], "line 0 0 0 1 1 1 c");
+}
+
+#[test]
+fn named_constants_are_looked_up() {
+    //the extra r g b intensities are allowed and don't change the reflections
+    let src = "constants a 1 2 3 4 5 6 7 8 9\nconstants b 1 2 3 4 5 6 7 8 9 10 20 30\n\
+               box a 0 0 0 1 1 1\nbox b 0 0 0 1 1 1";
+    let k = "box 0 0 0 1 1 1  // ka 1 4 7  kd 2 5 8  ks 3 6 9";
+    assert_eq!(commands(src)[..2], [k, k]);
+    let text = problems("box nope 0 0 0 1 1 1");
+    assert!(text.contains("E0007"), "{}", text);
+    assert!(text.contains("`nope`"), "{}", text);
+}
+
+#[test]
+fn the_invalid_examples_in_the_spec_are_errors() {
+    for src in ["rotate x|y 20", "rotate x y 33", "rotate x 33 [k1]"] {
+        let text = problems(src);
+        assert!(text.contains("could not run script"), "{}: {}", src, text);
+    }
+    for (src, code) in [
+        ("rotate w 30", "E0006"),
+        ("move 1 2 3 4 5", "E0003"),
+        ("shading glossy", "E0008"),
+        (
+            "constants c 1 2 3 4 5 6 7 8 9\nmesh c data/cube.obj",
+            "E0009",
+        ),
+        //only round shapes have a resolution
+        ("box resolution 4 0 0 0 1 1 1", "E0004"),
+        ("box 0 0 0 1 1 1 nowhere", "E0018"),
+    ] {
+        let text = problems(src);
+        assert!(text.contains(code), "{}: {}", src, text);
+    }
+}
+
+#[test]
+fn unsupported_commands_are_read_and_skipped() {
+    let lines = ["shading raytrace", "generate_rayfiles", "web"];
+    let src = lines.join("\n");
+    let mut diag = Diagnostics::with_source("test.mdl", &src);
+    let script = Script::from_mdl(&src, &mut diag);
+    assert!(script.is_some());
+    let text = diag.to_string();
+    assert_eq!(text.matches("W0001").count(), lines.len(), "{}", text);
+    assert_eq!(
+        script.unwrap(),
+        Script::from_mdl("", &mut Diagnostics::default()).unwrap()
+    );
+}
@@ -0,0 +1,134 @@
+use graphics::{
+    add_circle, add_sphere, add_torus, scale_matrix, steps_for_radius, Diagnostics, Modtrix,
+    Projection, RGB8Color, Script, Space, Tessellation, DEFAULT_RESOLUTION, MIN_RESOLUTION,
+};
+
+#[test]
+fn bigger_shapes_get_more_steps() {
+    assert_eq!(steps_for_radius(0.0), MIN_RESOLUTION);
+    assert!(steps_for_radius(10.0) < steps_for_radius(100.0));
+    assert_eq!(steps_for_radius(1e6), DEFAULT_RESOLUTION);
+    assert_eq!(steps_for_radius(f64::INFINITY), DEFAULT_RESOLUTION);
+}
+
+#[test]
+fn further_shapes_are_smaller_on_the_screen() {
+    let mut space = Space::<RGB8Color>::new();
+    assert_eq!(space.screen_radius((0.0, 0.0, -500.0), 50.0), 50.0);
+    space.set_eye((0.0, 0.0, 0.0));
+    space.set_projection(Projection::Perspective(100.0));
+    let near = space.screen_radius((0.0, 0.0, -200.0), 50.0);
+    let far = space.screen_radius((0.0, 0.0, -350.0), 50.0);
+    assert_eq!(near, 50.0 * 100.0 / 150.0);
+    assert_eq!(far, near / 2.0);
+}
+
+#[test]
+fn too_few_steps_are_rejected() {
+    for src in [
+        "resolution 3",
+        "sphere resolution 1.5 0 0 0 10",
+        "torus resolution 0 0 0 0 5 10",
+        "resolution tolerance 0",
+    ] {
+        let mut diag = Diagnostics::with_source("test.mdl", src);
+        assert!(
+            Script::from_mdl(src, &mut diag).is_none(),
+            "{} was accepted",
+            src
+        );
+        assert!(diag.to_string().contains("E0016"), "{}", diag);
+    }
+    let src =
+        "resolution auto\nsphere resolution 12 0 0 0 10\ntorus resolution tolerance 0.5 0 0 0 5 10";
+    let mut diag = Diagnostics::with_source("test.mdl", src);
+    assert!(Script::from_mdl(src, &mut diag).is_some(), "{}", diag);
+}
+
+#[test]
+fn curves_are_split_until_they_are_within_the_tolerance() {
+    let lines = |r: f64, tolerance: f64| {
+        let mut space = Space::<RGB8Color>::new();
+        let tess = Tessellation::Within {
+            tolerance,
+            transform: &Modtrix::IDENT,
+        };
+        add_circle(0.0, 0.0, 0.0, r, tess, &mut space);
+        space.line_count()
+    };
+    assert!(lines(10.0, 0.5) < lines(200.0, 0.5));
+    assert!(lines(200.0, 2.0) < lines(200.0, 0.5));
+    //a piece of a circle is at most r * (1 - cos(a / 2)) from it, half that at its middle
+    let n = lines(200.0, 0.5) as f64;
+    assert!(200.0 * (1.0 - (std::f64::consts::PI / n).cos()) <= 2.0 * 0.5);
+}
+
+#[test]
+fn surfaces_get_more_triangles_when_they_are_bigger_on_the_screen() {
+    let tris = |scale: f64| {
+        let mut space = Space::<RGB8Color>::new();
+        let mut m = Modtrix::IDENT;
+        Modtrix::multr(&mut m, &scale_matrix!(scale, scale, scale));
+        let tess = Tessellation::Within {
+            tolerance: 0.5,
+            transform: &m,
+        };
+        add_sphere(0.0, 0.0, 0.0, 20.0, tess, &mut space);
+        space.tri_count()
+    };
+    assert!(tris(1.0) < tris(5.0));
+    assert!(tris(5.0) < tris(20.0));
+    //the same sphere at the default resolution
+    assert!(tris(20.0) < 500 * 499);
+}
+
+#[test]
+fn the_fewest_steps_still_make_closed_shapes() {
+    //every edge of a closed surface is shared by two triangles going opposite ways, which
+    //cancel out, and the surface encloses some volume
+    let check = |space: Space<RGB8Color>, volume: f64| {
+        let key = |p: (f64, f64, f64)| {
+            let r = |x: f64| (x * 1e6).round() as i64;
+            (r(p.0), r(p.1), r(p.2))
+        };
+        let mut edges = std::collections::HashMap::new();
+        let mut found = 0.0;
+        for [a, b, c] in space.triangles() {
+            for (p, q) in [(a, b), (b, c), (c, a)] {
+                *edges.entry((key(p), key(q))).or_insert(0) += 1;
+                *edges.entry((key(q), key(p))).or_insert(0) -= 1;
+            }
+            let cross = (
+                b.1 * c.2 - b.2 * c.1,
+                b.2 * c.0 - b.0 * c.2,
+                b.0 * c.1 - b.1 * c.0,
+            );
+            found += (a.0 * cross.0 + a.1 * cross.1 + a.2 * cross.2) / 6.0;
+        }
+        assert!(edges.values().all(|&n| n == 0), "the surface has a hole");
+        assert!(found.abs() > volume / 4.0, "{} is too flat", found);
+    };
+
+    let mut space = Space::<RGB8Color>::new();
+    add_sphere(
+        0.0,
+        0.0,
+        0.0,
+        10.0,
+        Tessellation::Steps(MIN_RESOLUTION),
+        &mut space,
+    );
+    check(space, 4.0 / 3.0 * std::f64::consts::PI * 1000.0);
+
+    let mut space = Space::<RGB8Color>::new();
+    add_torus(
+        0.0,
+        0.0,
+        0.0,
+        5.0,
+        20.0,
+        Tessellation::Steps(MIN_RESOLUTION),
+        &mut space,
+    );
+    check(space, 2.0 * std::f64::consts::PI.powi(2) * 20.0 * 25.0);
+}
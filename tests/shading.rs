@@ -0,0 +1,79 @@
+use std::collections::HashSet;
+
+use graphics::space::draw_space;
+use graphics::{Light, RGB8Color, Screen, Shading, Space};
+
+const SIZE: usize = 40;
+
+///one triangle with its right angle at (5, 5), lit from above and to the side, with normals
+///leaning different ways at each corner so smooth shading has something to blend
+fn render(shading: Shading) -> Screen<RGB8Color> {
+    let mut space = Space::<RGB8Color>::new();
+    space.set_ambient_light((40, 40, 40).into());
+    space.set_ambient_reflection((0.5, 0.5, 0.5));
+    space.set_diffuse_reflection((0.5, 0.5, 0.5));
+    space.set_specular_reflection((0.5, 0.5, 0.5));
+    space.add_light(Light::new((30.0, 30.0, 60.0), (255, 255, 255).into()));
+    space.set_shading(shading);
+    let a = space.add_vertex((5.0, 5.0, 0.0), Some((-1.0, -1.0, 1.0)));
+    let b = space.add_vertex((35.0, 5.0, 0.0), Some((1.0, 0.0, 1.0)));
+    let c = space.add_vertex((5.0, 35.0, 0.0), Some((0.0, 1.0, 1.0)));
+    space.add_indexed_tri(a, b, c);
+    let mut screen = Screen::with_size(SIZE, SIZE);
+    draw_space(&space, &mut screen);
+    screen
+}
+
+///every pixel something was drawn to, as (x, y) with y going up, and its color
+fn drawn(screen: &Screen<RGB8Color>) -> Vec<((usize, usize), [u8; 3])> {
+    let ppm = screen.byte_vec();
+    //the pixels come after the header
+    ppm[ppm.len() - SIZE * SIZE * 3..]
+        .chunks(3)
+        .enumerate()
+        .filter(|(_, c)| c.iter().any(|&b| b != 0))
+        .map(|(i, c)| ((i % SIZE, SIZE - 1 - i / SIZE), [c[0], c[1], c[2]]))
+        .collect()
+}
+
+fn colors(screen: &Screen<RGB8Color>) -> HashSet<[u8; 3]> {
+    drawn(screen).into_iter().map(|(_, c)| c).collect()
+}
+
+#[test]
+fn flat_shading_fills_with_one_color() {
+    let screen = render(Shading::Flat);
+    assert!(drawn(&screen).len() > 300);
+    assert_eq!(colors(&screen).len(), 1);
+}
+
+#[test]
+fn smooth_shading_blends_across_the_face() {
+    let gouraud = render(Shading::Gouraud);
+    let phong = render(Shading::Phong);
+    let flat = drawn(&render(Shading::Flat));
+    for screen in [&gouraud, &phong] {
+        //the same pixels are covered, just not in one color
+        let pixels: Vec<_> = drawn(screen).into_iter().map(|(p, _)| p).collect();
+        assert_eq!(pixels, flat.iter().map(|&(p, _)| p).collect::<Vec<_>>());
+        assert!(colors(screen).len() > 10, "{}", colors(screen).len());
+    }
+    //phong lights each pixel so it isn't just gouraud's corner colors blended
+    assert_ne!(gouraud.byte_vec(), phong.byte_vec());
+}
+
+#[test]
+fn wireframes_only_draw_edges() {
+    let screen = render(Shading::Wireframe);
+    let pixels = drawn(&screen);
+    assert!(pixels.len() > 60);
+    for ((x, y), _) in pixels {
+        let (x, y) = (x as f64, y as f64);
+        //the two sides along the axes and the slanted one between them
+        let to_edge = (x - 5.0)
+            .abs()
+            .min((y - 5.0).abs())
+            .min((x + y - 40.0).abs() / 2f64.sqrt());
+        assert!(to_edge <= 1.0, "({}, {}) is inside the triangle", x, y);
+    }
+}
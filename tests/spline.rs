@@ -0,0 +1,123 @@
+mod common;
+
+use common::problems;
+use graphics::{Diagnostics, Easing, Engine, KnobError, RGB8Color, Script, Spline};
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-9
+}
+
+#[test]
+fn splines_go_through_their_keys_and_hold_the_ends() {
+    let splines = [
+        Spline::CatmullRom(vec![(0.0, 1.0), (10.0, 5.0), (30.0, -2.0)]),
+        Spline::Hermite(vec![(0.0, 1.0, 0.5), (10.0, 5.0, 0.0), (30.0, -2.0, -1.0)]),
+        Spline::Bezier(vec![
+            (0.0, 1.0),
+            (2.0, 9.0),
+            (5.0, 9.0),
+            (10.0, 5.0),
+            (20.0, 0.0),
+            (30.0, 0.0),
+            (30.0, -2.0),
+        ]),
+    ];
+    for s in splines {
+        assert_eq!(s.problem(), None);
+        assert!(close(s.value(0.0), 1.0), "{:?}", s);
+        assert!(close(s.value(10.0), 5.0), "{:?}", s);
+        assert!(close(s.value(30.0), -2.0), "{:?}", s);
+        assert!(close(s.value(-5.0), 1.0), "{:?}", s);
+        assert!(close(s.value(100.0), -2.0), "{:?}", s);
+    }
+}
+
+#[test]
+fn splines_have_the_slopes_they_are_given() {
+    let slope = |s: &Spline, f: f64| (s.value(f + 1e-4) - s.value(f - 1e-4)) / 2e-4;
+    let hermite = Spline::Hermite(vec![(0.0, 0.0, 2.0), (10.0, 0.0, -3.0)]);
+    assert!((slope(&hermite, 0.0001) - 2.0).abs() < 1e-3);
+    assert!((slope(&hermite, 9.9999) + 3.0).abs() < 1e-3);
+    //catmull-rom takes the slope at the middle key from the keys on either side
+    let catmull = Spline::CatmullRom(vec![(0.0, 0.0), (10.0, 7.0), (20.0, 4.0)]);
+    assert!((slope(&catmull, 10.0) - 0.2).abs() < 1e-3);
+    //a bezier with its control points a third of the way along is a straight line
+    let line = Spline::Bezier(vec![(0.0, 0.0), (4.0, 4.0), (8.0, 8.0), (12.0, 12.0)]);
+    assert!(close(line.value(3.0), 3.0));
+    assert!(close(line.value(7.5), 7.5));
+}
+
+#[test]
+fn bad_keys_are_problems() {
+    let problem = |s: Spline| s.problem().unwrap_or_default();
+    assert!(problem(Spline::CatmullRom(vec![(0.0, 1.0)])).contains("at least 2"));
+    assert!(problem(Spline::CatmullRom(vec![(5.0, 1.0), (5.0, 2.0)])).contains("order"));
+    assert!(problem(Spline::Bezier(vec![(0.0, 1.0), (1.0, 1.0), (2.0, 2.0)])).contains("3"));
+    let outside = Spline::Bezier(vec![(0.0, 1.0), (-1.0, 1.0), (2.0, 2.0), (3.0, 2.0)]);
+    assert!(problem(outside).contains("control point at frame -1"));
+}
+
+#[test]
+fn splines_with_problems_are_not_added() {
+    let mut eng = Engine::<RGB8Color>::with_screen_dims(10, 10);
+    let one_key = eng.add_spline("k".into(), Spline::CatmullRom(vec![(0.0, 5.0)]));
+    assert_eq!(
+        one_key,
+        Err(KnobError::Spline(
+            "a spline needs at least 2 keys, found 1".into()
+        ))
+    );
+    //the knob is left as it was instead of panicking when it is looked up
+    eng.set_knob("k", 3.0);
+    assert_eq!(eng.get_knob_val("k".into(), 0), 3.0);
+}
+
+#[test]
+fn vary_is_used_over_a_spline() {
+    let mut eng = Engine::<RGB8Color>::with_screen_dims(10, 10);
+    let spline = || Spline::CatmullRom(vec![(0.0, 0.0), (20.0, 20.0)]);
+    eng.add_spline("k".into(), spline()).unwrap();
+    eng.add_spline("j".into(), spline()).unwrap();
+    eng.add_vary("k".into(), 5, 10, 100.0, 200.0, Easing::Linear, 21)
+        .unwrap();
+    assert_eq!(eng.get_knob_val("k".into(), 2), 100.0);
+    assert_eq!(eng.get_knob_val("k".into(), 15), 200.0);
+    assert!(close(eng.get_knob_val("j".into(), 15), 15.0));
+}
+
+#[test]
+fn splines_are_read_from_scripts() {
+    let src = "frames 30\nbasename s\n\
+               spline a catmull-rom 0 0 10 1 29 0\n\
+               spline b hermite 0 0 1 29 1 0\n\
+               spline c bezier 0 0 5 1 10 1 15 0\n\
+               move 1 1 1 a\nmove 1 1 1 b\nmove 1 1 1 c";
+    let mut diag = Diagnostics::with_source("test.mdl", src);
+    let script = Script::from_mdl(src, &mut diag).expect("failed to parse script");
+    assert!(diag.is_empty(), "{}", diag);
+    let text = script.disassemble();
+    assert!(
+        text.contains("spline a catmull-rom 0 0 10 1 29 0\n"),
+        "{}",
+        text
+    );
+    assert!(text.contains("spline b hermite 0 0 1 29 1 0\n"), "{}", text);
+    assert!(
+        text.contains("spline c bezier 0 0 5 1 10 1 15 0\n"),
+        "{}",
+        text
+    );
+}
+
+#[test]
+fn bad_splines_are_reported() {
+    let text = problems("frames 10\nbasename s\nspline a wiggly 0 0 9 1");
+    assert!(text.contains("E0021"), "{}", text);
+    assert!(text.contains("`wiggly`"), "{}", text);
+    let text = problems("frames 10\nbasename s\nspline a hermite 0 0 1 9 1");
+    assert!(text.contains("E0021"), "{}", text);
+    let text = problems("frames 10\nbasename s\nspline a catmull-rom 9 0 0 1");
+    assert!(text.contains("E0021"), "{}", text);
+    let text = problems("spline a catmull-rom 0 0 9 1");
+    assert!(text.contains("E0011"), "{}", text);
+}